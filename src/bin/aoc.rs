@@ -0,0 +1,60 @@
+use aoc23::prelude::*;
+
+static USAGE: &str = "usage: aoc cache <doctor|encrypt>";
+
+fn cache_doctor() -> Result<()> {
+    let reports = utils::aoc::cache_doctor()?;
+    let session = utils::aoc::session_fingerprint(&get_config().aoc_session);
+    let mut flagged = 0;
+    for report in reports.iter() {
+        let provenance = match &report.metadata {
+            Some(metadata) if metadata.session_fingerprint == session => {
+                format!(" [fetched {} by this session]", metadata.fetched_at)
+            }
+            Some(metadata) => format!(
+                " [fetched {} by session {}]",
+                metadata.fetched_at, metadata.session_fingerprint
+            ),
+            None => " [no metadata]".to_owned(),
+        };
+        match &report.problem {
+            Some(problem) => {
+                flagged += 1;
+                println!("{}: {problem}{provenance}", report.path.display());
+            }
+            None => println!("{}: ok{provenance}", report.path.display()),
+        }
+    }
+    println!("{} files checked, {flagged} suspicious", reports.len());
+    if flagged > 0 {
+        return Err("suspicious files in the puzzle input cache".into());
+    }
+    Ok(())
+}
+
+fn cache_encrypt() -> Result<()> {
+    let encrypted = utils::aoc::encrypt_cache()?;
+    for path in encrypted.iter() {
+        println!("encrypted {}", path.display());
+    }
+    println!(
+        "{} files encrypted with {}",
+        encrypted.len(),
+        utils::aoc::CacheKey::path()?.display()
+    );
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["cache", "doctor"] => cache_doctor(),
+        ["cache", "encrypt"] => cache_encrypt(),
+        _ => Err(USAGE.into()),
+    }
+}
@@ -1,104 +1,279 @@
 use aoc23::prelude::*;
+use std::{collections::VecDeque, time::Instant};
 
-fn part1(input: String) -> Result<usize> {
-    let mut sum = 0;
-    for line in input.lines().into_iter() {
-        let chars = line.chars();
-        let first_num: usize = chars
-            .clone()
-            .into_iter()
-            .skip_while(|c| !c.is_digit(10))
-            .take(1)
-            // This would get continuous digits: .take_while(|c| c.is_digit(10))
-            .collect::<String>()
-            .parse()?;
-        let last_num: usize = chars
-            .into_iter()
-            .rev()
-            .skip_while(|c| !c.is_digit(10))
-            .take(1)
-            .collect::<String>()
-            .parse()?;
-        let value = first_num * 10 + last_num;
-        sum += value;
-    }
+static DAY: u8 = 1;
+
+static SPELLED: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// A digit found in a line, with the byte position its match starts at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Found {
+    digit: u32,
+    pos: usize,
+}
 
-    // println!("Answer: {}", sum);
-    Ok(sum)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Calibration {
+    first: Found,
+    last: Found,
 }
 
-fn num_to_word(num: u32) -> Option<&'static str> {
-    match num {
-        1 => Some("one"),
-        2 => Some("two"),
-        3 => Some("three"),
-        4 => Some("four"),
-        5 => Some("five"),
-        6 => Some("six"),
-        7 => Some("seven"),
-        8 => Some("eight"),
-        9 => Some("nine"),
-        _ => None,
+impl Calibration {
+    fn value(&self) -> u32 {
+        self.first.digit * 10 + self.last.digit
     }
 }
 
-fn first_occurrence(str: &str, reversed: bool) -> Option<u32> {
-    if reversed {
-        for (i, char) in str.chars().rev().enumerate() {
-            let digit = char.to_digit(10).or_else(|| {
-                for num in 1..=9 {
-                    if let Some(num_word) = num_to_word(num) {
-                        if i + 1 >= num_word.len()
-                            && &str[str.len() - i - 1..str.len()][..num_word.len()] == num_word
-                        {
-                            return Some(num);
-                        }
-                    }
+/// Aho-Corasick automaton over the digit patterns, turned into a full transition table so each
+/// byte is one lookup. Matches may overlap, so `eightwo` yields both 8 and 2.
+struct Decoder {
+    transitions: Vec<[usize; 256]>,
+    /// The digit and pattern length matched on entering a state, if any. No pattern is a suffix
+    /// of another, so a state never needs more than one.
+    outputs: Vec<Option<(u32, usize)>>,
+}
+
+impl Decoder {
+    fn new(spelled: bool) -> Self {
+        let mut patterns: Vec<(String, u32)> = (0..=9).map(|d| (d.to_string(), d)).collect();
+        if spelled {
+            patterns.extend(
+                SPELLED
+                    .iter()
+                    .zip(1..)
+                    .map(|(word, d)| (word.to_string(), d)),
+            );
+        }
+
+        // Trie first, with 0 as "no edge" since nothing can point back at the root.
+        let mut trie = vec![[0usize; 256]];
+        let mut outputs = vec![None];
+        for (pattern, digit) in patterns {
+            let mut state = 0;
+            for byte in pattern.bytes() {
+                if trie[state][byte as usize] == 0 {
+                    trie.push([0; 256]);
+                    outputs.push(None);
+                    trie[state][byte as usize] = trie.len() - 1;
                 }
-                None
-            });
-            if digit.is_some() {
-                return digit;
+                state = trie[state][byte as usize];
             }
+            outputs[state] = Some((digit, pattern.len()));
         }
-    } else {
-        for (i, char) in str.chars().enumerate() {
-            let digit = char.to_digit(10).or_else(|| {
-                for num in 1..=9 {
-                    if let Some(num_word) = num_to_word(num) {
-                        if i + 1 >= num_word.len() && &str[i + 1 - num_word.len()..=i] == num_word {
-                            return Some(num);
-                        }
-                    }
+
+        // Breadth first, every missing edge follows the failure link, which is already complete.
+        let mut transitions = trie.clone();
+        let mut fail = vec![0; trie.len()];
+        let mut queue: VecDeque<usize> = trie[0].iter().copied().filter(|s| *s != 0).collect();
+        while let Some(state) = queue.pop_front() {
+            if outputs[state].is_none() {
+                outputs[state] = outputs[fail[state]];
+            }
+            for byte in 0..256 {
+                let child = trie[state][byte];
+                if child == 0 {
+                    transitions[state][byte] = transitions[fail[state]][byte];
+                } else {
+                    fail[child] = if state == 0 {
+                        0
+                    } else {
+                        transitions[fail[state]][byte]
+                    };
+                    queue.push_back(child);
                 }
-                None
-            });
-            if digit.is_some() {
-                return digit;
             }
         }
+        Self {
+            transitions,
+            outputs,
+        }
     }
-    None
-}
 
-fn part_2(input: String) -> Result<u32> {
-    let mut sum = 0;
-    for line in input.lines().into_iter() {
-        let first_num = first_occurrence(line, false).ok_or("No number found in line")?;
-        let last_num = first_occurrence(line, true).ok_or("No number found in line reversed")?;
-        let value = first_num * 10 + last_num;
-        sum += value;
-        println!("Line {line}: a:{first_num}|b:{last_num} = {value} ++ {sum}");
+    /// Every digit in the line, in order of where they end.
+    fn scan<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Found> + 'a {
+        line.bytes()
+            .enumerate()
+            .scan(0, |state, (idx, byte)| {
+                *state = self.transitions[*state][byte as usize];
+                Some(self.outputs[*state].map(|(digit, len)| Found {
+                    digit,
+                    pos: idx + 1 - len,
+                }))
+            })
+            .flatten()
     }
-    Ok(sum)
+
+    fn decode(&self, line: &str) -> Option<Calibration> {
+        let mut found = self.scan(line);
+        let first = found.next()?;
+        let last = found.last().unwrap_or(first);
+        Some(Calibration { first, last })
+    }
+
+    fn sum(&self, input: &str) -> Result<u32> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                self.decode(line)
+                    .map(|calibration| calibration.value())
+                    .ok_or_else(|| format!("no digit on line {}: {line:?}", idx + 1).into())
+            })
+            .sum()
+    }
+}
+
+fn part1(input: String) -> Result<u32> {
+    let start = Instant::now();
+    let answer = Decoder::new(false).sum(&input)?;
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
+}
+
+fn part2(input: String) -> Result<u32> {
+    let start = Instant::now();
+    let answer = Decoder::new(true).sum(&input)?;
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!("Day 1");
+    println!("Day {DAY}");
     println!("=====");
-    let input = utils::aoc::get_puzzle_input(1).await?;
-    println!("part 1: {}", part1(input.clone())?);
-    println!("part 2: {}", part_2(input.clone())?);
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    part2(input.clone())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    static DATA_1: &str = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+
+    static DATA_2: &str = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+
+    /// The first revised part 2: peek for a digit, otherwise look for a word starting here, then
+    /// walk back from the end looking for a word ending there.
+    fn part_2_peek(line: &str) -> u32 {
+        let numbers = SPELLED.iter().zip(1..).collect::<Vec<_>>();
+        let mut chars = line.chars();
+        let mut peek_index = 0;
+        let first_num = loop {
+            if let Some(Some(peek_digit)) = line.chars().nth(peek_index).map(|c| c.to_digit(10)) {
+                break peek_digit;
+            }
+            if let Some((_, digit)) = numbers.iter().find(|num| chars.as_str().starts_with(num.0)) {
+                break *digit;
+            }
+            if chars.next().is_none() {
+                break 0;
+            }
+            peek_index += 1;
+        };
+        let last_num = loop {
+            if let Some((_, digit)) = numbers.iter().find(|num| chars.as_str().ends_with(num.0)) {
+                break *digit;
+            }
+            if let Some(last_char) = chars.next_back() {
+                if let Some(digit) = last_char.to_digit(10) {
+                    break digit;
+                }
+            } else {
+                break first_num;
+            }
+        };
+        first_num * 10 + last_num
+    }
+
+    /// The second revised part 2: digits are just more patterns next to the words.
+    fn part_2_no_peek(line: &str) -> u32 {
+        let numbers = SPELLED
+            .iter()
+            .map(|word| word.to_string())
+            .zip(1..)
+            .chain((1..=9).map(|d| (d.to_string(), d)))
+            .collect::<Vec<_>>();
+        let mut chars = line.chars();
+        let first_num = loop {
+            if let Some((_, digit)) = numbers
+                .iter()
+                .find(|num| chars.as_str().starts_with(&num.0))
+            {
+                break *digit;
+            }
+            if chars.next().is_none() {
+                break 0;
+            }
+        };
+        let last_num = loop {
+            if let Some((_, digit)) = numbers.iter().find(|num| chars.as_str().ends_with(&num.0)) {
+                break *digit;
+            }
+            if chars.next_back().is_none() {
+                break first_num;
+            }
+        };
+        first_num * 10 + last_num
+    }
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA_1.to_owned())?, 142);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA_2.to_owned())?, 281);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let decoder = Decoder::new(true);
+        assert_eq!(
+            decoder.decode("eightwo"),
+            Some(Calibration {
+                first: Found { digit: 8, pos: 0 },
+                last: Found { digit: 2, pos: 4 },
+            })
+        );
+        assert_eq!(decoder.decode("xoneightx").map(|c| c.value()), Some(18));
+        assert_eq!(decoder.decode("sevenine").map(|c| c.value()), Some(79));
+        assert_eq!(Decoder::new(false).decode("eightwo"), None);
+        assert_eq!(
+            Decoder::new(false).decode("a0b").map(|c| c.value()),
+            Some(0)
+        );
+        assert_eq!(decoder.decode("zero0nine").map(|c| c.value()), Some(9));
+        assert!(part1("abc".to_owned()).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn decoder_matches_revised_strategies(line in "[a-z0-9]{0,4}(one|two|three|four|five|six|seven|eight|nine|[a-z]|[0-9]){1,8}[a-z0-9]{0,4}") {
+            let value = Decoder::new(true).decode(&line).map(|c| c.value()).unwrap_or(0);
+            prop_assert_eq!(value, part_2_peek(&line));
+            // The no peek strategy only ever listed 1 to 9, so it skips a literal 0.
+            if !line.contains('0') {
+                prop_assert_eq!(value, part_2_no_peek(&line));
+            }
+        }
+    }
+}
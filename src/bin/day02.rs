@@ -1,7 +1,9 @@
 use aoc23::prelude::*;
-use std::{collections::HashMap, fmt::Display, str::FromStr};
+use std::{cmp::Ordering, str::FromStr, time::Instant};
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+static DAY: u8 = 2;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 enum Cube {
     Blue,
     Red,
@@ -9,126 +11,225 @@ enum Cube {
 }
 
 impl FromStr for Cube {
-    type Err = String;
+    type Err = BoxE;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s {
             "blue" => Ok(Cube::Blue),
             "red" => Ok(Cube::Red),
             "green" => Ok(Cube::Green),
-            _ => Err("asd".into()),
+            unknown => Err(format!("unknown color {unknown:?}").into()),
+        }
+    }
+}
+
+/// A handful of cubes, or the contents of a bag.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+struct CubeSet {
+    red: u32,
+    green: u32,
+    blue: u32,
+}
+
+impl CubeSet {
+    fn new(red: u32, green: u32, blue: u32) -> Self {
+        Self { red, green, blue }
+    }
+
+    fn count_mut(&mut self, cube: Cube) -> &mut u32 {
+        match cube {
+            Cube::Red => &mut self.red,
+            Cube::Green => &mut self.green,
+            Cube::Blue => &mut self.blue,
         }
     }
+
+    /// Elementwise max, the smallest set that contains both.
+    fn max(&self, other: &Self) -> Self {
+        Self {
+            red: self.red.max(other.red),
+            green: self.green.max(other.green),
+            blue: self.blue.max(other.blue),
+        }
+    }
+
+    fn power(&self) -> u32 {
+        self.red * self.green * self.blue
+    }
+}
+
+/// Elementwise: a set is less than another only if no color has more, so sets with more of one
+/// color and less of another are not comparable.
+impl PartialOrd for CubeSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let orderings = [
+            self.red.cmp(&other.red),
+            self.green.cmp(&other.green),
+            self.blue.cmp(&other.blue),
+        ];
+        orderings
+            .into_iter()
+            .try_fold(Ordering::Equal, |acc, ord| match (acc, ord) {
+                (acc, Ordering::Equal) => Some(acc),
+                (Ordering::Equal, ord) => Some(ord),
+                (acc, ord) if acc == ord => Some(acc),
+                _ => None,
+            })
+    }
+}
+
+impl FromStr for CubeSet {
+    type Err = BoxE;
+
+    /// `3 blue, 4 red`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut set = Self::default();
+        for part in s.split(',') {
+            let (count, color) = part
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| format!("expected `<count> <color>`, got {:?}", part.trim()))?;
+            let count = count
+                .parse::<u32>()
+                .map_err(|e| format!("bad count {count:?}: {e}"))?;
+            *set.count_mut(color.trim().parse()?) += count;
+        }
+        Ok(set)
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 struct Game {
     id: u32,
-    sets: Vec<HashMap<Cube, u32>>,
+    sets: Vec<CubeSet>,
+}
+
+impl Game {
+    /// Fails on the first bad line, saying which one.
+    fn parse_all(input: &str) -> Result<Vec<Self>> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                line.parse()
+                    .map_err(|e| format!("line {}: {e}: {line:?}", idx + 1).into())
+            })
+            .collect()
+    }
+
+    /// Fewest cubes of each color the bag could have held for this game.
+    fn minimum_bag(&self) -> CubeSet {
+        self.sets
+            .iter()
+            .fold(CubeSet::default(), |bag, set| bag.max(set))
+    }
+
+    fn possible_with(&self, bag: &CubeSet) -> bool {
+        self.minimum_bag() <= *bag
+    }
 }
 
 impl FromStr for Game {
-    type Err = String;
+    type Err = BoxE;
 
-    // Game 1: 7 green, 4 blue, 3 red; 4 blue, 10 red, 1 green; 1 blue, 9 red Game 2:
-    // 2 red, 4 blue, 3 green; 5 green, 3 red, 1 blue; 3 green, 5 blue, 3 red Game 3:
-    // 12 red, 1 blue; 6 red, 2 green, 3 blue; 2 blue, 5 red, 3 green
+    /// `Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green`
     fn from_str(line: &str) -> std::result::Result<Self, Self::Err> {
-        let parts: Vec<&str> = line.split(':').collect();
-        let id = parts[0]
+        let (id, sets) = line.split_once(':').ok_or("no `:` after game id")?;
+        let id = id
             .trim()
-            .split_whitespace()
-            .last()
-            .ok_or("no id")?
+            .strip_prefix("Game ")
+            .ok_or("line does not start with `Game`")?
             .parse::<u32>()
-            .ok()
-            .ok_or("not a number")?;
-        let sets = parts[1]
-            .split(';')
-            .map(|set| {
-                set.split(',')
-                    .filter_map(|part| {
-                        let mut iter = part.trim().split_whitespace();
-                        let count = iter.next().unwrap().parse::<u32>().unwrap();
-                        let color = iter.next().unwrap().parse::<Cube>().ok()?;
-                        Some((color, count))
-                    })
-                    .collect()
-            })
-            .collect();
-        Ok(Game { id, sets })
+            .map_err(|e| format!("bad game id: {e}"))?;
+        Ok(Game {
+            id,
+            sets: sets.split(';').map(str::parse).collect::<Result<_>>()?,
+        })
     }
 }
 
-fn part1(input: String) -> Result<impl Display> {
-    let games: Vec<Game> = input
-        .lines()
-        .into_iter()
-        .map(|l| l.parse().expect("failed to parse line"))
-        .collect();
-    let mut possible_game_id_sum = 0;
-    let limit_red = 12;
-    let limit_green = 13;
-    let limit_blue = 14;
-    for game in games {
-        let out_of_bounds = game.sets.iter().any(|set| match set.get(&Cube::Red) {
-            Some(red) => red > &limit_red,
-            None => false,
-        } || match set.get(&Cube::Green) {
-            Some(green) => green > &limit_green,
-            None => false,
-        } || match set.get(&Cube::Blue) {
-            Some(blue) => blue > &limit_blue,
-            None => false,
-        });
-        if !out_of_bounds {
-            // println!("Valid game id, {:?}", game);
-            possible_game_id_sum += game.id;
-        } else {
-            // println!("INVALID game {:?}", game);
-        }
-    }
-    Ok(possible_game_id_sum)
+/// Games that could have been played with `bag`.
+fn possible_games<'a>(games: &'a [Game], bag: &'a CubeSet) -> impl Iterator<Item = &'a Game> {
+    games.iter().filter(|game| game.possible_with(bag))
 }
 
-fn part2(input: String) -> Result<impl Display> {
-    let games: Vec<Game> = input
-        .lines()
-        .into_iter()
-        .map(|l| l.parse().expect("failed to parse line"))
-        .collect();
-    let mut power_sum = 0;
-    for game in games {
-        let mut maxes: HashMap<Cube, u32> = [(Cube::Red, 1), (Cube::Green, 1), (Cube::Blue, 1)]
-            .into_iter()
-            .collect();
-        for set in game.sets.iter() {
-            for (cube, &count) in set {
-                let max_count = maxes.get_mut(cube).ok_or("default missing")?;
-                *max_count = u32::max(*max_count, count);
-            }
-        }
-        let power = maxes.values().into_iter().fold(1u32, |acc, v| acc * v);
-        power_sum += power;
-    }
-    Ok(power_sum)
+fn part1(input: String) -> Result<u32> {
+    let start = Instant::now();
+    let games = Game::parse_all(&input)?;
+    let bag = CubeSet::new(12, 13, 14);
+    let answer = possible_games(&games, &bag).map(|game| game.id).sum();
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
+}
+
+fn part2(input: String) -> Result<u32> {
+    let start = Instant::now();
+    let answer = Game::parse_all(&input)?
+        .iter()
+        .map(|game| game.minimum_bag().power())
+        .sum();
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!("Day 1");
+    println!("Day {DAY}");
     println!("=====");
-    let input = utils::aoc::get_puzzle_input(2).await?;
-
-    // ```
-    // let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-    // Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-    // Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-    // Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-    // Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"
-    //     .to_owned();
-    // ```
-    println!("part 1: {}", part1(input.clone())?);
-    println!("part 2: {}", part2(input.clone())?);
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    part2(input.clone())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA.to_owned())?, 2286);
+        Ok(())
+    }
+
+    #[test]
+    fn test_queries() -> Result<()> {
+        let games = Game::parse_all(DATA)?;
+        assert_eq!(games[0].minimum_bag(), CubeSet::new(4, 2, 6));
+        let bag = CubeSet::new(6, 3, 6);
+        let ids = possible_games(&games, &bag)
+            .map(|g| g.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 5]);
+
+        assert!(CubeSet::new(1, 2, 3) < CubeSet::new(1, 2, 4));
+        assert_eq!(
+            CubeSet::new(1, 5, 3).partial_cmp(&CubeSet::new(2, 2, 3)),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let err = Game::parse_all("Game 1: 1 red\nGame 2: 3 purple").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("line 2: unknown color \"purple\""));
+        let err = Game::parse_all("Game 1: x red").unwrap_err();
+        assert!(err.to_string().starts_with("line 1: bad count \"x\""));
+    }
+}
@@ -1,189 +1,214 @@
 use aoc23::prelude::*;
-use std::str::FromStr;
+use grid::Grid;
+use std::{collections::BTreeMap, ops::RangeInclusive, str::FromStr, time::Instant};
 
-#[derive(Debug)]
-enum Item {
-    Digit(u32),
-    Symbol(bool),
-    None,
-}
+static DAY: u8 = 3;
 
-#[derive(Debug)]
-struct Number {
-    pub value: usize,
-    pub row_index: usize,
-    pub char_start: usize,
-    pub char_end: usize,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Cell {
+    #[default]
+    Empty,
+    Digit(u8),
+    Symbol(char),
 }
 
-struct Grid {
-    pub rows: Vec<Vec<Item>>,
-    pub numbers: Vec<Number>,
+/// A number in the schematic: one row, a run of digit cols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Span {
+    value: usize,
+    row: usize,
+    cols: RangeInclusive<usize>,
 }
 
-impl Grid {
-    fn get_item(&self, row: usize, col: usize) -> Option<&Item> {
-        match self.rows.get(row) {
-            Some(row) => row.get(col),
-            None => None,
-        }
+impl Span {
+    /// Cells touching the number, diagonals included, clamped to the grid. Returned as
+    /// `(top left, bottom right)`, inclusive.
+    fn bounding_box(&self, size: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+        (
+            (
+                self.row.saturating_sub(1),
+                self.cols.start().saturating_sub(1),
+            ),
+            (
+                (self.row + 1).min(size.0 - 1),
+                (self.cols.end() + 1).min(size.1 - 1),
+            ),
+        )
     }
+}
+
+struct Schematic {
+    grid: Grid<Cell>,
+    numbers: Vec<Span>,
+    /// Each symbol position to the indices of the numbers around it, every number once.
+    adjacency: BTreeMap<(usize, usize), Vec<usize>>,
+}
 
-    fn get_adjacent_items(
-        &self,
-        row: usize,
-        start: usize,
-        end: usize,
-    ) -> Vec<(&Item, usize, usize)> {
-        let row_start = if row > 0 { row - 1 } else { 0 };
-        let col_start = if start > 0 { start - 1 } else { 0 };
-        let mut items: Vec<(&Item, usize, usize)> = vec![];
-        for row_i in row_start..=row + 1 {
-            for col_i in col_start..=end + 1 {
-                if row_i != row || col_i < start || col_i > end {
-                    let item = self.get_item(row_i, col_i);
-                    if let Some(item) = item {
-                        items.push((item, row_i, col_i));
+impl Schematic {
+    fn spans(grid: &Grid<Cell>) -> Vec<Span> {
+        let mut numbers = vec![];
+        for row in 0..grid.rows() {
+            let mut current: Option<Span> = None;
+            // One col past the edge closes a number that runs to the right side.
+            for col in 0..=grid.cols() {
+                match (grid.get(row, col), current.as_mut()) {
+                    (Some(Cell::Digit(digit)), Some(span)) => {
+                        span.value = span.value * 10 + *digit as usize;
+                        span.cols = *span.cols.start()..=col;
                     }
+                    (Some(Cell::Digit(digit)), None) => {
+                        current = Some(Span {
+                            value: *digit as usize,
+                            row,
+                            cols: col..=col,
+                        })
+                    }
+                    _ => numbers.extend(current.take()),
                 }
             }
         }
-        return items;
+        numbers
     }
 
-    fn get_adjacent_numbers(&self, row: usize, col: usize) -> Vec<&Number> {
-        let mut unique_numbers: Vec<&Number> = vec![];
-        for item in self.get_adjacent_items(row, col, col) {
-            match item {
-                (Item::Digit(_), r, c) => {
-                    if let Some(number_at) = self
-                        .numbers
-                        .iter()
-                        .find(|n| n.row_index == r && c >= n.char_start && c <= n.char_end)
-                    {
-                        if unique_numbers.iter().all(|n| n.value != number_at.value) {
-                            unique_numbers.push(number_at);
-                        }
+    fn build_adjacency(
+        grid: &Grid<Cell>,
+        numbers: &[Span],
+    ) -> BTreeMap<(usize, usize), Vec<usize>> {
+        let mut adjacency: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+        for (idx, number) in numbers.iter().enumerate() {
+            let ((top, left), (bottom, right)) = number.bounding_box(grid.size());
+            for row in top..=bottom {
+                for col in left..=right {
+                    if let Cell::Symbol(_) = grid[(row, col)] {
+                        adjacency.entry((row, col)).or_default().push(idx);
                     }
                 }
-                _ => {}
             }
         }
-        unique_numbers
+        adjacency
+    }
+
+    fn part_numbers(&self) -> impl Iterator<Item = &Span> {
+        let mut is_part = vec![false; self.numbers.len()];
+        self.adjacency
+            .values()
+            .flatten()
+            .for_each(|idx| is_part[*idx] = true);
+        self.numbers
+            .iter()
+            .zip(is_part)
+            .filter_map(|(number, is_part)| is_part.then_some(number))
+    }
+
+    /// Product of the two numbers around each `*` that touches exactly two.
+    fn gear_ratios(&self) -> impl Iterator<Item = usize> + '_ {
+        self.adjacency
+            .iter()
+            .filter(|((row, col), around)| {
+                self.grid[(*row, *col)] == Cell::Symbol('*') && around.len() == 2
+            })
+            .map(|(_, around)| around.iter().map(|idx| self.numbers[*idx].value).product())
     }
 }
 
-impl FromStr for Grid {
+impl FromStr for Schematic {
     type Err = BoxE;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut grid = Grid {
-            rows: vec![],
-            numbers: vec![],
-        };
-        for (row_index, line) in s.lines().enumerate() {
-            let mut row: Vec<Item> = vec![];
-            let mut num: Option<usize> = None;
-            let mut char_start = 0;
-            for (char_index, char) in line.trim().chars().enumerate() {
-                if let Some(digit) = char.to_digit(10) {
-                    row.push(Item::Digit(digit));
-                    num = if let Some(existing_num) = num {
-                        Some(existing_num * 10 + digit as usize)
-                    } else {
-                        char_start = char_index;
-                        Some(digit as usize)
-                    };
-                } else {
-                    if char == '.' {
-                        row.push(Item::None);
-                    } else {
-                        row.push(Item::Symbol(char == '*'))
-                    }
-                    if let Some(value) = num {
-                        grid.numbers.push(Number {
-                            value,
-                            row_index,
-                            char_start,
-                            char_end: char_index - 1,
-                        });
-                        num = None;
-                        char_start = 0;
-                    }
-                }
-            }
-
-            // This killed me, I forgot to add a check after the end of the line to see if I
-            // was still "scanning" for a number and adding it. There was 1 single number not
-            // getting parsed that was on the right edge of the grid and I could not figure it
-            // out for a while.
-            if let Some(value) = num {
-                grid.numbers.push(Number {
-                    value,
-                    row_index,
-                    char_start,
-                    char_end: line.len() - 1,
-                });
+        let mut grid = Grid::new(0, 0);
+        for (row, line) in s.trim().lines().enumerate() {
+            let line = line.trim();
+            if grid.rows() > 0 && line.len() != grid.cols() {
+                return Err(format!("schematic row {row} is not {} wide", grid.cols()).into());
             }
-            grid.rows.push(row);
+            grid.push_row(
+                line.chars()
+                    .map(|c| match c {
+                        '.' => Cell::Empty,
+                        '0'..='9' => Cell::Digit(c as u8 - b'0'),
+                        symbol => Cell::Symbol(symbol),
+                    })
+                    .collect(),
+            );
         }
-        Ok(grid)
+        let numbers = Self::spans(&grid);
+        let adjacency = Self::build_adjacency(&grid, &numbers);
+        Ok(Self {
+            grid,
+            numbers,
+            adjacency,
+        })
     }
 }
 
 fn part1(input: String) -> Result<usize> {
-    let grid: Grid = input.parse()?;
-    let mut sum = 0;
-    for number in grid.numbers.iter() {
-        let adjacent =
-            grid.get_adjacent_items(number.row_index, number.char_start, number.char_end);
-        let is_part_no = adjacent
-            .iter()
-            .any(|item| matches!(item, (Item::Symbol(_), _, _)));
-        if is_part_no {
-            sum += number.value;
-        }
-    }
-    Ok(sum)
+    let start = Instant::now();
+    let schematic: Schematic = input.parse()?;
+    let answer = schematic.part_numbers().map(|number| number.value).sum();
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
 }
 
 fn part2(input: String) -> Result<usize> {
-    let grid: Grid = input.parse()?;
-    let mut sum = 0;
-    let mut used: Vec<(usize, usize)> = vec![];
-    for number in grid.numbers.iter() {
-        let is_used = used
-            .iter()
-            .any(|(row, char_start)| number.row_index == *row && number.char_start == *char_start);
-        if !is_used {
-            let adjacent =
-                grid.get_adjacent_items(number.row_index, number.char_start, number.char_end);
-            if let Some((_, row, col)) = adjacent
-                .iter()
-                .find(|item| matches!(item.0, Item::Symbol(true)))
-            {
-                let gear_nums = grid.get_adjacent_numbers(*row, *col);
-                if gear_nums.len() >= 2 {
-                    // this is lazy.. adds duplicates but w/e
-                    let power = gear_nums.iter().fold(1, |pow, gear| pow * gear.value);
-                    gear_nums
-                        .iter()
-                        .for_each(|g| used.push((g.row_index, g.char_start)));
-                    sum += power;
-                }
-            }
-        }
-    }
-    Ok(sum)
+    let start = Instant::now();
+    let schematic: Schematic = input.parse()?;
+    let answer = schematic.gear_ratios().sum();
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!("Day 3");
+    println!("Day {DAY}");
     println!("=====");
-    let input = utils::aoc::get_puzzle_input(3).await?;
-    let part_1 = part1(input.clone())?;
-    println!("part 1: {}", part_1);
-    println!("part 2: {}", part2(input.clone())?);
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    part2(input.clone())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 4361);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA.to_owned())?, 467835);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spans_and_adjacency() -> Result<()> {
+        let schematic: Schematic = "..12\n*.3*\n45..".parse()?;
+        let values = schematic
+            .numbers
+            .iter()
+            .map(|n| n.value)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![12, 3, 45]);
+        assert_eq!(schematic.numbers[0].cols, 2..=3);
+        assert_eq!(schematic.numbers[0].bounding_box((3, 4)), ((0, 1), (1, 3)));
+        assert_eq!(schematic.adjacency[&(1, 0)], vec![2]);
+        // 3 and 12 both touch the right edge `*`, and 12 is listed once despite two digits.
+        assert_eq!(schematic.adjacency[&(1, 3)], vec![0, 1]);
+        assert_eq!(schematic.gear_ratios().collect::<Vec<_>>(), vec![36]);
+        Ok(())
+    }
+}
@@ -1,124 +1,209 @@
 use aoc23::prelude::*;
-use itertools::Itertools;
-use std::{collections::VecDeque, str::FromStr, time::Instant};
+use std::{str::FromStr, time::Instant};
 
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
-struct Card {
-    id: usize,
-    part_1_score: usize,
-    part_2_matches: usize,
-    part_2_count: usize,
-}
+static DAY: u8 = 4;
 
-impl FromStr for Card {
+/// Scratchcard numbers are below 100, so a set of them fits in one `u128`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct NumberSet(u128);
+
+impl FromStr for NumberSet {
     type Err = BoxE;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut id: Option<usize> = None;
-        let mut winning_numbers: Vec<usize> = vec![];
-        let mut score = 0;
-        let mut matches = 0;
-        for part in s.split(':') {
-            if id.is_some() {
-                for (num_mode, wins_or_nums) in part.split('|').enumerate() {
-                    wins_or_nums
-                        .trim()
-                        .split_whitespace()
-                        .into_iter()
-                        .map(|num| {
-                            num.parse::<usize>()
-                                .expect(&format!("could not parse number: {}", num))
-                        })
-                        .for_each(|num| {
-                            if num_mode == 0 {
-                                winning_numbers.push(num);
-                            } else {
-                                if winning_numbers.iter().any(|winner| winner == &num) {
-                                    matches += 1;
-                                    score = if score == 0 { 1 } else { score * 2 };
-                                }
-                            }
-                        });
-                }
-            } else {
-                id = Some(
-                    part.split_whitespace()
-                        .last()
-                        .ok_or("Failed to get last item")?
-                        .parse()?,
-                )
+        s.split_whitespace().try_fold(Self::default(), |set, num| {
+            let num = num
+                .parse::<u32>()
+                .map_err(|e| format!("could not parse number {num:?}: {e}"))?;
+            if num >= 100 {
+                return Err(format!("number {num} does not fit the set").into());
             }
-        }
-        Ok(Card {
-            id: id.ok_or("no id found")?,
-            part_1_score: score,
-            part_2_matches: matches,
-            part_2_count: 1,
+            Ok(Self(set.0 | 1 << num))
         })
     }
 }
 
-fn part1(input: String) -> Result<usize> {
-    Ok(input
-        .lines()
-        .map(|line| line.parse::<Card>())
-        .filter_map(|card| card.ok())
-        .fold(0, |sum, card| sum + card.part_1_score))
+impl NumberSet {
+    fn matches(&self, other: &Self) -> usize {
+        (self.0 & other.0).count_ones() as usize
+    }
 }
 
-fn part2(input: String) -> Result<usize> {
-    let mut sum = 0;
-    let cards = input
-        .lines()
-        .map(|line| line.parse::<Card>())
-        .filter_map(|card| card.ok())
-        .collect_vec();
-    let mut queue: VecDeque<&Card> = cards.iter().collect();
-    while let Some(card) = queue.pop_front() {
-        sum += 1;
-        for card_index in card.id..card.id + card.part_2_matches {
-            cards.get(card_index).map(|c| queue.push_back(&c));
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Card {
+    id: usize,
+    matches: usize,
+}
+
+impl Card {
+    fn score(&self) -> usize {
+        match self.matches {
+            0 => 0,
+            matches => 1 << (matches - 1),
         }
     }
-    Ok(sum)
+
+    fn parse_all(input: &str) -> Result<Vec<Self>> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl FromStr for Card {
+    type Err = BoxE;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (id, numbers) = s
+            .split_once(':')
+            .ok_or_else(|| format!("no card id in {s:?}"))?;
+        let (winning, have) = numbers
+            .split_once('|')
+            .ok_or_else(|| format!("no `|` in {s:?}"))?;
+        Ok(Card {
+            id: id
+                .split_whitespace()
+                .last()
+                .ok_or("Failed to get last item")?
+                .parse()?,
+            matches: winning.parse::<NumberSet>()?.matches(&have.parse()?),
+        })
+    }
 }
 
-fn part2_revised(input: String) -> Result<usize> {
-    let mut sum = 0;
-    let mut cards = input
-        .lines()
-        .map(|line| line.parse::<Card>())
-        .filter_map(|card| card.ok())
-        .collect_vec();
-    for i in 0..cards.len() {
-        let (count, matches) = {
-            let card = cards.get(i).ok_or("card not found")?;
-            sum += card.part_2_count;
-            (card.part_2_count, card.part_2_matches)
-        };
-        for card_index in 1..=matches {
-            cards.get_mut(i + card_index).map(|card_below| {
-                card_below.part_2_count += count;
-            });
+/// Total cards held once every win has copied the cards below it. Copies only ever flow down,
+/// so one pass in order sees each card's final count before it hands out its own copies.
+fn total_cards(cards: &[Card]) -> usize {
+    let mut copies = vec![1; cards.len()];
+    for (idx, card) in cards.iter().enumerate() {
+        let below = (idx + 1)..(idx + 1 + card.matches).min(cards.len());
+        for next in below {
+            copies[next] += copies[idx];
         }
     }
-    Ok(sum)
+    copies.iter().sum()
+}
+
+fn part1(input: String) -> Result<usize> {
+    let start = Instant::now();
+    let answer = Card::parse_all(&input)?.iter().map(Card::score).sum();
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
+}
+
+fn part2(input: String) -> Result<usize> {
+    let start = Instant::now();
+    let answer = total_cards(&Card::parse_all(&input)?);
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    Ok(answer)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!("Day 4");
+    println!("Day {DAY}");
     println!("=====");
-    let input = utils::aoc::get_puzzle_input(4).await?;
-    let start = Instant::now();
-    println!("part 1: {}\t[{:?}]", part1(input.clone())?, start.elapsed());
-    let start = Instant::now();
-    println!("part 2: {}\t[{:?}]", part2(input.clone())?, start.elapsed());
-    let start = Instant::now();
-    println!(
-        "part 2: {}\t[{:?}]\t[revised]",
-        part2_revised(input.clone())?,
-        start.elapsed()
-    );
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    part2(input.clone())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use proptest::prelude::*;
+    use std::collections::VecDeque;
+
+    static DATA: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 13);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA.to_owned())?, 30);
+        Ok(())
+    }
+
+    /// The original matching: linear search of the winning numbers.
+    fn matches_by_search(winning: &[usize], have: &[usize]) -> usize {
+        have.iter().filter(|num| winning.contains(num)).count()
+    }
+
+    /// The original part 2: queue every copy and count them as they come off.
+    fn total_cards_by_queue(cards: &[Card]) -> usize {
+        let mut sum = 0;
+        let mut queue: VecDeque<&Card> = cards.iter().collect();
+        while let Some(card) = queue.pop_front() {
+            sum += 1;
+            for card_index in card.id..card.id + card.matches {
+                if let Some(c) = cards.get(card_index) {
+                    queue.push_back(c);
+                }
+            }
+        }
+        sum
+    }
+
+    /// The original revised part 2: push each card's count onto the cards below it.
+    fn total_cards_by_counts(cards: &[Card]) -> usize {
+        let mut counts = vec![1; cards.len()];
+        let mut sum = 0;
+        for i in 0..cards.len() {
+            let count = counts[i];
+            sum += count;
+            for card_index in 1..=cards[i].matches {
+                if let Some(below) = counts.get_mut(i + card_index) {
+                    *below += count;
+                }
+            }
+        }
+        sum
+    }
+
+    fn card_numbers() -> impl Strategy<Value = (Vec<usize>, Vec<usize>)> {
+        (
+            prop::collection::hash_set(0usize..100, 0..6),
+            prop::collection::hash_set(0usize..100, 0..10),
+        )
+            .prop_map(|(winning, have)| (winning.into_iter().collect(), have.into_iter().collect()))
+    }
+
+    proptest! {
+        #[test]
+        fn cascade_matches_old_part_2(numbers in prop::collection::vec(card_numbers(), 0..12)) {
+            let input = numbers
+                .iter()
+                .enumerate()
+                .map(|(idx, (winning, have))| {
+                    format!("Card {}: {} | {}", idx + 1, winning.iter().join(" "), have.iter().join(" "))
+                })
+                .join("\n");
+            let cards = Card::parse_all(&input).unwrap();
+            for (card, (winning, have)) in cards.iter().zip(&numbers) {
+                prop_assert_eq!(card.matches, matches_by_search(winning, have));
+            }
+            let total = total_cards(&cards);
+            prop_assert_eq!(total, total_cards_by_queue(&cards));
+            prop_assert_eq!(total, total_cards_by_counts(&cards));
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_numbers() {
+        assert!("Card 1: 100 | 1".parse::<Card>().is_err());
+        assert!("Card 1: 1 x | 1".parse::<Card>().is_err());
+        assert!("Card 1: 1 2 3".parse::<Card>().is_err());
+    }
+}
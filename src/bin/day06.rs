@@ -1,5 +1,6 @@
 use aoc23::prelude::*;
 use derive_builder::Builder;
+use std::time::Instant;
 
 static DAY: u8 = 6;
 
@@ -10,100 +11,160 @@ struct Race {
 }
 
 impl Race {
-    fn get_roots(&self) -> (usize, usize) {
-        let a = -1.0;
-        let b = self.time as f64;
-        let c = -(self.record_distance as f64);
-        let n1 = (-b + f64::sqrt(b * b - (4.0 * a * c))) / (2.0 * a);
-        let n2 = (-b - f64::sqrt(b * b - (4.0 * a * c))) / (2.0 * a);
-        if n1 < n2 {
-            // floor and round up to ensure we win
-            let n1 = f64::floor(n1) as usize + 1;
-
-            // ceil and round down to ensure we don't exceed time cap
-            let n2 = f64::ceil(n2) as usize - 1;
-            (n1, n2)
-        } else {
-            let n1 = f64::ceil(n1) as usize - 1;
-            let n2 = f64::floor(n2) as usize + 1;
-            (n2, n1)
+    /// Reads the `Time:` / `Distance:` table into one race per column.
+    fn parse_races(s: &str) -> Result<Vec<Self>> {
+        let (times, distances) = Self::parse_rows(s)?;
+        let times = times
+            .split_whitespace()
+            .map(|n| n.parse::<usize>())
+            .collect::<SResult<Vec<_>, _>>()?;
+        let distances = distances
+            .split_whitespace()
+            .map(|n| n.parse::<usize>())
+            .collect::<SResult<Vec<_>, _>>()?;
+        if times.len() != distances.len() {
+            return Err(format!(
+                "{} times but {} distances in race table",
+                times.len(),
+                distances.len()
+            )
+            .into());
         }
+        Ok(times
+            .into_iter()
+            .zip(distances)
+            .map(|(time, record_distance)| Race {
+                time,
+                record_distance,
+            })
+            .collect())
+    }
+
+    /// Reads the table with bad kerning fixed, every column joined into one big race.
+    fn parse_kerned_race(s: &str) -> Result<Self> {
+        let (times, distances) = Self::parse_rows(s)?;
+        Ok(Race {
+            time: times.replace(char::is_whitespace, "").parse()?,
+            record_distance: distances.replace(char::is_whitespace, "").parse()?,
+        })
+    }
+
+    fn parse_rows(s: &str) -> Result<(&str, &str)> {
+        let mut lines = s.trim().lines();
+        let times = lines
+            .next()
+            .and_then(|l| l.trim().strip_prefix("Time:"))
+            .ok_or("missing Time: row")?;
+        let distances = lines
+            .next()
+            .and_then(|l| l.trim().strip_prefix("Distance:"))
+            .ok_or("missing Distance: row")?;
+        Ok((times, distances))
+    }
+
+    /// First and last hold time that beat the record, if any do.
+    ///
+    /// Holding for `h` travels `h * (time - h)`, so winners are the integers where
+    /// `h^2 - time*h + record < 0`.
+    fn get_roots(&self) -> Option<(usize, usize)> {
+        utils::math::quadratic_integer_bounds(1, -(self.time as i128), self.record_distance as i128)
+            .map(|(low, high)| (low.max(0) as usize, high.min(self.time as i128) as usize))
     }
 
     fn get_ways_to_beat_count(&self) -> usize {
-        let (n1, n2) = self.get_roots();
-        if self.time < n1 {
-            0
-        } else {
-            let n2 = usize::min(self.time, n2);
-            n2 - n1 + 1
-        }
+        self.get_roots()
+            .map(|(low, high)| high - low + 1)
+            .unwrap_or(0)
     }
 }
 
-fn get_ways_to_beat_in_all_races(races: &[Race]) -> Result<usize> {
-    let mut answer = 1;
-    for race in races {
-        let ways_to_beat = race.get_ways_to_beat_count();
-        answer *= ways_to_beat;
-    }
+fn get_ways_to_beat_in_all_races(races: &[Race]) -> usize {
+    races.iter().map(Race::get_ways_to_beat_count).product()
+}
+
+fn part1(input: String) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let races = Race::parse_races(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = get_ways_to_beat_in_all_races(&races);
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+fn part2(input: String) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let race = Race::parse_kerned_race(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = race.get_ways_to_beat_count();
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
     Ok(answer)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // let input = utils::aoc::get_puzzle_input(DAY).await?;
     println!("Day {DAY}");
     println!("=====");
-    let races: [Race; 3] = [
-        Race {
-            time: 7,
-            record_distance: 9,
-        },
-        Race {
-            time: 15,
-            record_distance: 40,
-        },
-        Race {
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    part2(input.clone())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 288);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA.to_owned())?, 71503);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roots_are_exact() {
+        // Exactly hitting the record is not a win, roots land on integers here.
+        let race = Race {
             time: 30,
             record_distance: 200,
-        },
-    ];
-    println!(
-        "part 1, example: {}",
-        get_ways_to_beat_in_all_races(&races)?
-    );
-    let races: [Race; 4] = [
-        Race {
-            time: 53,
-            record_distance: 250,
-        },
-        Race {
-            time: 91,
-            record_distance: 1330,
-        },
-        Race {
-            time: 67,
-            record_distance: 1081,
-        },
-        Race {
-            time: 68,
-            record_distance: 1025,
-        },
-    ];
-    println!("part 1, real: {}", get_ways_to_beat_in_all_races(&races)?);
-    let races: [Race; 1] = [Race {
-        time: 71530,
-        record_distance: 940200,
-    }];
-    println!(
-        "part 2, example: {}",
-        get_ways_to_beat_in_all_races(&races)?
-    );
-    let races: [Race; 1] = [Race {
-        time: 53916768,
-        record_distance: 250133010811025,
-    }];
-    println!("part 2, real: {}", get_ways_to_beat_in_all_races(&races)?);
-    Ok(())
+        };
+        assert_eq!(race.get_roots(), Some((11, 19)));
+        let unbeatable = Race {
+            time: 4,
+            record_distance: 4,
+        };
+        assert_eq!(unbeatable.get_roots(), None);
+        // Large enough that f64 cannot represent the discriminant exactly.
+        let big = Race {
+            time: 4_000_000_000,
+            record_distance: 3_999_999_999_999_999_999,
+        };
+        assert_eq!(big.get_roots(), Some((2_000_000_000, 2_000_000_000)));
+    }
 }
@@ -1,9 +1,8 @@
 use aoc23::prelude::*;
-use derive_builder::Builder;
 use itertools::Itertools;
-use std::{cmp::Ordering, str::FromStr, time::Instant};
+use std::{str::FromStr, time::Instant};
 
-#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 enum Strength {
     HighCard,
     OnePair,
@@ -14,173 +13,128 @@ enum Strength {
     FiveOfAKind,
 }
 
-impl Strength {
-    #[cfg(feature = "part2")]
-    fn for_hand(hand: &Hand) -> Self {
-        let mut counts = [0; 15];
-        let mut jokers = 0;
-        for card in hand.cards.iter() {
-            if card == &1 {
-                jokers += 1;
-            } else {
-                counts[*card as usize - 2] += 1;
-            }
-        }
-        let mut strength = counts
-            .iter()
-            .fold(Strength::HighCard, |strength, &count| match count {
-                5 => Strength::FiveOfAKind,
-                4 => Strength::FourOfAKind,
-                3 => match strength {
-                    Strength::TwoPair => Strength::FullHouse,
-                    Strength::OnePair => Strength::FullHouse,
-                    _ => Strength::ThreeOfAKind,
-                },
-                2 => match strength {
-                    Strength::ThreeOfAKind => Strength::FullHouse,
-                    Strength::OnePair => Strength::TwoPair,
-                    _ => Strength::OnePair,
-                },
-                _ => strength,
-            });
-        while jokers > 0 {
-            strength = match strength {
-                Strength::HighCard => Strength::OnePair,
-                Strength::OnePair => Strength::ThreeOfAKind,
-                Strength::TwoPair => Strength::FullHouse,
-                Strength::ThreeOfAKind => Strength::FourOfAKind,
-                Strength::FullHouse => Strength::FourOfAKind,
-                Strength::FourOfAKind => Strength::FiveOfAKind,
-                Strength::FiveOfAKind => Strength::FiveOfAKind,
-            };
-            jokers -= 1;
-        }
-        strength
+/// Rules for a game of Camel Cards: how cards rank against each other and which card, if any,
+/// is wild when classifying a hand.
+#[derive(Debug, Clone)]
+struct CamelCards {
+    /// Short name shown next to the answer.
+    name: &'static str,
+    /// Every card label, weakest first.
+    order: &'static str,
+    wildcard: Option<char>,
+}
+
+impl CamelCards {
+    const STANDARD: Self = Self {
+        name: "standard",
+        order: "23456789TJQKA",
+        wildcard: None,
+    };
+
+    /// `J` is a joker: weakest card on its own, but stands in for whatever makes the best hand.
+    const JOKERS: Self = Self {
+        name: "jokers",
+        order: "J23456789TQKA",
+        wildcard: Some('J'),
+    };
+
+    fn rank(&self, card: char) -> Result<u32> {
+        self.order
+            .find(card)
+            .map(|idx| idx as u32)
+            .ok_or_else(|| format!("invalid card: {card}").into())
     }
 
-    #[cfg(feature = "part1")]
-    fn for_hand(hand: &Hand) -> Self {
-        let mut counts = [0; 15];
-        for card in hand.cards.iter() {
-            counts[*card as usize - 2] += 1;
+    fn classify(&self, cards: &str) -> Strength {
+        let wild = cards.chars().filter(|c| Some(*c) == self.wildcard).count();
+        let mut counts = cards
+            .chars()
+            .filter(|c| Some(*c) != self.wildcard)
+            .counts()
+            .into_values()
+            .sorted_unstable_by(|a, b| b.cmp(a));
+        // Wildcards always do best joining the biggest group.
+        let most = counts.next().unwrap_or(0) + wild;
+        let second = counts.next().unwrap_or(0);
+        match (most, second) {
+            (5, _) => Strength::FiveOfAKind,
+            (4, _) => Strength::FourOfAKind,
+            (3, 2) => Strength::FullHouse,
+            (3, _) => Strength::ThreeOfAKind,
+            (2, 2) => Strength::TwoPair,
+            (2, _) => Strength::OnePair,
+            _ => Strength::HighCard,
         }
-        counts
-            .iter()
-            .fold(Strength::HighCard, |strength, &count| match count {
-                5 => Strength::FiveOfAKind,
-                4 => Strength::FourOfAKind,
-                3 => match strength {
-                    Strength::TwoPair => Strength::FullHouse,
-                    Strength::OnePair => Strength::FullHouse,
-                    _ => Strength::ThreeOfAKind,
-                },
-                2 => match strength {
-                    Strength::ThreeOfAKind => Strength::FullHouse,
-                    Strength::OnePair => Strength::TwoPair,
-                    _ => Strength::OnePair,
-                },
-                _ => strength,
+    }
+
+    /// Packs strength and then each card's rank into one number that sorts like the hand: 4
+    /// bits for strength followed by 4 bits per card, first card most significant.
+    fn sort_key(&self, hand: &Hand) -> Result<u32> {
+        hand.cards
+            .chars()
+            .try_fold(self.classify(&hand.cards) as u32, |key, card| {
+                Ok((key << 4) | self.rank(card)?)
             })
     }
 }
 
-#[derive(Debug, Builder, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Hand {
-    cards: Vec<u32>,
+    cards: String,
     bid: usize,
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let self_strength = Strength::for_hand(self);
-        let other_strength = Strength::for_hand(other);
-        match self_strength.cmp(&other_strength) {
-            Ordering::Equal => self
-                .cards
-                .iter()
-                .zip(other.cards.iter())
-                .map(|(s, o)| s.cmp(o))
-                .skip_while(|ord| matches!(ord, Ordering::Equal))
-                .next()
-                .unwrap_or(Ordering::Equal),
-            Ordering::Less => Ordering::Less,
-            Ordering::Greater => Ordering::Greater,
-        }
-    }
-}
-
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 impl FromStr for Hand {
     type Err = BoxE;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut splits = s.trim().split_whitespace();
-        Ok(HandBuilder::default()
-            .cards(
-                splits
-                    .next()
-                    .unwrap()
-                    .chars()
-                    .map(|c| {
-                        if let Some(digit) = c.to_digit(10) {
-                            digit
-                        } else {
-                            match c {
-                                #[cfg(feature = "part1")]
-                                'A' => 14,
-                                #[cfg(feature = "part2")]
-                                'A' => 13,
-                                #[cfg(feature = "part1")]
-                                'K' => 13,
-                                #[cfg(feature = "part2")]
-                                'K' => 12,
-                                #[cfg(feature = "part1")]
-                                'Q' => 12,
-                                #[cfg(feature = "part2")]
-                                'Q' => 11,
-                                #[cfg(feature = "part1")]
-                                'J' => 11,
-                                #[cfg(feature = "part2")]
-                                'J' => 1,
-                                'T' => 10,
-                                _ => panic!("invalid card: {}", c),
-                            }
-                        }
-                    })
-                    .collect(),
-            )
-            .bid(splits.next().unwrap().parse()?)
-            .build()?)
+        let (cards, bid) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("hand without a bid: {s:?}"))?;
+        if cards.chars().count() != 5 {
+            return Err(format!("hand must have 5 cards: {cards:?}").into());
+        }
+        Ok(Hand {
+            cards: cards.to_owned(),
+            bid: bid.trim().parse()?,
+        })
     }
 }
 
-fn calculate(input: String) -> Result<usize> {
-    let start = Instant::now();
-    let answer = input
+fn total_winnings(input: &str, rules: &CamelCards) -> Result<usize> {
+    let hands: Vec<(u32, Hand)> = input
         .lines()
-        .map(|line| line.parse::<Hand>().unwrap())
-        .sorted()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let hand = line.parse::<Hand>()?;
+            Ok((rules.sort_key(&hand)?, hand))
+        })
+        .collect::<Result<_>>()?;
+    Ok(hands
+        .into_iter()
+        .sorted_unstable_by_key(|(key, _)| *key)
         .enumerate()
-        .map(|(idx, hand)| hand.bid * (idx + 1))
-        .sum();
+        .map(|(idx, (_, hand))| hand.bid * (idx + 1))
+        .sum())
+}
+
+fn calculate(input: String, rules: &CamelCards) -> Result<usize> {
+    let start = Instant::now();
+    let answer = total_winnings(&input, rules)?;
     let algo_time = start.elapsed();
-    println!("Day {DAY}: {answer}");
+    println!("Day {DAY} ({}): {answer}", rules.name);
     println!("\t{algo_time:?}");
     Ok(answer)
 }
 
-// TODO come back and revise for a faster solution
 #[tokio::main]
 async fn main() -> Result<()> {
     println!("Day {DAY}");
     println!("=====");
     let input = utils::aoc::get_puzzle_input(DAY).await?;
-    calculate(input.clone())?;
+    calculate(input.clone(), &CamelCards::STANDARD)?;
+    calculate(input.clone(), &CamelCards::JOKERS)?;
     Ok(())
 }
 
@@ -190,7 +144,7 @@ static DAY: u8 = 7;
 mod tests {
     use super::*;
 
-    static DATA: &'static str = "32T3K 765
+    static DATA: &str = "32T3K 765
 T55J5 684
 KK677 28
 KTJJT 220
@@ -198,10 +152,35 @@ QQQJA 483";
 
     #[test]
     fn test() -> Result<()> {
-        #[cfg(feature = "part1")]
-        assert_eq!(calculate(DATA.to_owned())?, 6440);
-        #[cfg(feature = "part2")]
-        assert_eq!(calculate(DATA.to_owned())?, 5905);
+        assert_eq!(calculate(DATA.to_owned(), &CamelCards::STANDARD)?, 6440);
+        assert_eq!(calculate(DATA.to_owned(), &CamelCards::JOKERS)?, 5905);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules_side_by_side() -> Result<()> {
+        let standard = CamelCards::STANDARD;
+        let jokers = CamelCards::JOKERS;
+        for (cards, with_standard, with_jokers) in [
+            ("KTJJT", Strength::TwoPair, Strength::FourOfAKind),
+            ("T55J5", Strength::ThreeOfAKind, Strength::FourOfAKind),
+            ("JJJJJ", Strength::FiveOfAKind, Strength::FiveOfAKind),
+            ("2345J", Strength::HighCard, Strength::OnePair),
+            ("2233J", Strength::TwoPair, Strength::FullHouse),
+        ] {
+            assert_eq!(standard.classify(cards), with_standard, "{cards}");
+            assert_eq!(jokers.classify(cards), with_jokers, "{cards}");
+        }
+
+        // Same strength, then the first card decides: J beats 2 normally, loses as a joker.
+        let j_first = "J2222 1".parse::<Hand>()?;
+        let two_first = "2JJJJ 1".parse::<Hand>()?;
+        assert!(standard.sort_key(&j_first)? > standard.sort_key(&two_first)?);
+        assert!(jokers.sort_key(&j_first)? < jokers.sort_key(&two_first)?);
+        assert!("XXXXX 1"
+            .parse::<Hand>()
+            .and_then(|h| standard.sort_key(&h))
+            .is_err());
         Ok(())
     }
 }
@@ -1,7 +1,6 @@
 use aoc23::prelude::*;
 use derive_builder::Builder;
 use itertools::Itertools;
-use rayon::prelude::*;
 use std::{collections::HashMap, time::Instant};
 
 extern crate regex;
@@ -108,6 +107,102 @@ fn part1(input: String) -> Result<usize> {
     Ok(answer)
 }
 
+/// Where one ghost ends up visiting `..Z` nodes. Its walk is fully determined by the (node,
+/// instruction index) state, so it is a lead-in followed by a loop that repeats forever.
+#[derive(Debug, Clone)]
+struct GhostWalk {
+    /// First step that is part of the repeating loop.
+    cycle_start: u64,
+    cycle_len: u64,
+    /// Steps on a `..Z` node, every hit before the loop and one pass of the loop.
+    z_hits: Vec<u64>,
+}
+
+impl GhostWalk {
+    fn analyse(map: &Map, start: &str) -> Result<Self> {
+        let mut seen: HashMap<(&str, usize), u64> = HashMap::new();
+        let mut z_hits = vec![];
+        let mut current = map.nodes.get(start).ok_or("no start node")?;
+        let mut step: u64 = 0;
+        loop {
+            let instruction = step as usize % map.dirs.len();
+            if let Some(first_seen) = seen.insert((current.id, instruction), step) {
+                return Ok(Self {
+                    cycle_start: first_seen,
+                    cycle_len: step - first_seen,
+                    z_hits,
+                });
+            }
+            if current.id.ends_with('Z') {
+                z_hits.push(step);
+            }
+            let next = match map.dirs[instruction] {
+                Dir::Left => current.left,
+                Dir::Right => current.right,
+            };
+            current = map.nodes.get(next).ok_or("missing node")?;
+            step += 1;
+        }
+    }
+
+    /// Just taking the lcm of first hits is only right when the single `..Z` hit lands exactly
+    /// on the loop length, then every hit is a multiple of it.
+    fn lcm_shortcut_holds(&self) -> bool {
+        self.z_hits == [self.cycle_len] && self.cycle_start <= self.cycle_len
+    }
+
+    fn lead_in_hits(&self) -> impl Iterator<Item = &u64> {
+        self.z_hits.iter().filter(|h| **h < self.cycle_start)
+    }
+
+    fn cycle_hits(&self) -> impl Iterator<Item = &u64> {
+        self.z_hits.iter().filter(|h| **h >= self.cycle_start)
+    }
+
+    fn hits_at(&self, step: u64) -> bool {
+        if step < self.cycle_start {
+            self.z_hits.contains(&step)
+        } else {
+            let in_cycle = self.cycle_start + (step - self.cycle_start) % self.cycle_len;
+            self.z_hits.contains(&in_cycle)
+        }
+    }
+}
+
+/// First step every ghost is on a `..Z` node, without assuming anything about the input.
+///
+/// If it happens during some ghost's lead-in it must be one of that ghost's lead-in hits, so
+/// those are checked directly. Otherwise every ghost is in its loop, and picking one loop hit
+/// per ghost gives a system of congruences for CRT to solve.
+fn first_common_hit(walks: &[GhostWalk]) -> Option<u64> {
+    let lead_in = walks
+        .iter()
+        .flat_map(GhostWalk::lead_in_hits)
+        .filter(|step| walks.iter().all(|w| w.hits_at(**step)))
+        .min()
+        .copied();
+
+    let in_cycles = walks
+        .iter()
+        .map(|w| w.cycle_hits().copied().collect_vec())
+        .multi_cartesian_product()
+        .filter_map(|hits| {
+            let congruences = hits
+                .iter()
+                .zip(walks)
+                .map(|(hit, w)| (*hit as i128, w.cycle_len as i128))
+                .collect_vec();
+            let (x, modulus) = utils::math::crt(&congruences)?;
+            // Smallest solution that is not before any of the chosen hits.
+            let latest = *hits.iter().max()? as i128;
+            let periods = ((latest - x).max(0) + modulus - 1) / modulus;
+            u64::try_from(x + periods * modulus).ok()
+        })
+        .min();
+
+    lead_in.into_iter().chain(in_cycles).min()
+}
+
 fn part2(input: String) -> Result<u64> {
     // parse
     let start = Instant::now();
@@ -116,29 +211,24 @@ fn part2(input: String) -> Result<u64> {
 
     // algo
     let start = Instant::now();
-    let currents: Vec<&Node> = map.nodes.values().filter(|n| n.id.ends_with("A")).collect();
-    let steps: Vec<u64> = currents
-        .par_iter()
-        .map(|node| {
-            let mut dir = map.dirs.iter().cycle();
-            let mut steps = 0;
-            let mut current = *node;
-            while !current.id.ends_with("Z") {
-                match dir.next().unwrap() {
-                    Dir::Left => current = map.nodes.get(current.left).expect("no left node"),
-                    Dir::Right => current = map.nodes.get(current.right).expect("no right node"),
-                }
-                steps += 1;
-                // println!("Step: {answer}"); let _ = std::io::stdout().flush();
-            }
-            steps
-        })
-        .collect();
-    let answer = utils::math::find_lcm(&steps[..]);
+    let walks: Vec<GhostWalk> = map
+        .nodes
+        .keys()
+        .filter(|id| id.ends_with('A'))
+        .map(|id| GhostWalk::analyse(&map, id))
+        .try_collect()?;
+    let shortcut = walks.iter().all(GhostWalk::lcm_shortcut_holds);
+    let answer = if shortcut {
+        let cycles = walks.iter().map(|w| w.cycle_len).collect_vec();
+        utils::math::find_lcm(&cycles).ok_or("lcm of cycles overflowed u64")?
+    } else {
+        first_common_hit(&walks).ok_or("ghosts are never all on ..Z at once")?
+    };
     let algo_time = start.elapsed();
 
     // output
     println!("Day {DAY}, part 2: {answer}");
+    println!("\tlcm shortcut holds: {shortcut}");
     println!("\tparse: {parsed_time:?}");
     println!("\talgo: {algo_time:?}");
     Ok(answer)
@@ -194,8 +284,9 @@ ZZZ = (ZZZ, ZZZ)"
 
     #[test]
     fn test_part_2() -> Result<()> {
-        part2(
-            "LR
+        assert_eq!(
+            part2(
+                "LR
 
 11A = (11B, XXX)
 11B = (XXX, 11Z)
@@ -205,8 +296,30 @@ ZZZ = (ZZZ, ZZZ)"
 22C = (22Z, 22Z)
 22Z = (22B, 22B)
 XXX = (XXX, XXX)"
-                .to_owned(),
+                    .to_owned(),
+            )?,
+            6
         );
         Ok(())
     }
+
+    #[test]
+    fn test_part_2_without_lcm_structure() -> Result<()> {
+        // 11A hits ..Z on every odd step, 22A sits on 22Z from step 2 onwards. Their first hits
+        // lcm to 2, but the first step both are on ..Z is 3.
+        let input = "L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22Z, 22Z)";
+        let map = Map::new(input)?;
+        let walk = GhostWalk::analyse(&map, "11A")?;
+        assert_eq!((walk.cycle_start, walk.cycle_len), (1, 2));
+        assert!(!walk.lcm_shortcut_holds());
+        assert_eq!(part2(input.to_owned())?, 3);
+        Ok(())
+    }
 }
@@ -1,98 +1,57 @@
 use aoc23::prelude::*;
-use derive_builder::Builder;
-use itertools::Itertools;
-use rayon::prelude::*;
-use std::{arch::x86_64::_MM_FROUND_CUR_DIRECTION, str::FromStr, time::Instant};
+use std::time::Instant;
+use utils::math::sequence;
 
 static DAY: u8 = 9;
 
-fn part1(input: String) -> Result<i64> {
-    let start = Instant::now();
-    let answer: i64 = input
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Forward,
+    Backward,
+}
+
+/// Lazily parses one history per line so a bad number surfaces as an error for that line
+/// instead of a panic.
+fn parse_histories(input: &str) -> impl Iterator<Item = Result<Vec<i64>>> + '_ {
+    input
         .lines()
-        .par_bridge()
-        .map(|line| {
-            let mut sequences: Vec<Vec<i64>> = vec![];
-            line.trim()
-                .split_whitespace()
-                .map(|s| s.parse::<i64>().expect("Failed to parse number in input"))
-                .enumerate()
-                .for_each(|(idx, num)| {
-                    // every new number of primary history can result in a new row of depth
-                    sequences.push(vec![]);
-                    for depth in 0..sequences.len() {
-                        if depth == 0 {
-                            sequences
-                                .get_mut(depth)
-                                .expect("top history expected")
-                                .push(num);
-                        } else {
-                            let len = sequences[depth].len();
-                            let above = &sequences[depth - 1];
-                            let left = *above.get(len).expect("expected value left");
-                            let right = *above.get(len + 1).expect("expected value right");
-                            sequences
-                                .get_mut(depth)
-                                .expect("seq current depth expect")
-                                .push(right - left);
-                        }
-                    }
-                });
-            sequences
-                .iter()
-                .rev()
-                .skip_while(|seq| seq.iter().all(|n| n == &0))
-                .fold(0, |acc, seq| seq.last().expect("expected last value") + acc)
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            line.split_whitespace()
+                .map(|n| {
+                    n.parse::<i64>()
+                        .map_err(|e| format!("line {}: bad number {n:?}: {e}", idx + 1).into())
+                })
+                .collect()
         })
-        .sum();
+}
+
+fn extrapolate(history: &[i64], direction: Direction) -> i64 {
+    (match direction {
+        Direction::Forward => sequence::extrapolate_forward(history, 1),
+        Direction::Backward => sequence::extrapolate_backward(history, 1),
+    }) as i64
+}
+
+fn sum_extrapolated(input: &str, direction: Direction) -> Result<i64> {
+    parse_histories(input)
+        .map(|history| history.map(|h| extrapolate(&h, direction)))
+        .sum()
+}
+
+fn part1(input: String) -> Result<i64> {
+    let start = Instant::now();
+    let answer = sum_extrapolated(&input, Direction::Forward)?;
 
     // output
     println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
     Ok(answer)
 }
 
-// algo
 fn part2(input: String) -> Result<i64> {
     let start = Instant::now();
-    let answer: i64 = input
-        .lines()
-        .par_bridge()
-        .map(|line| {
-            let mut sequences: Vec<Vec<i64>> = vec![];
-            line.trim()
-                .split_whitespace()
-                .map(|s| s.parse::<i64>().expect("Failed to parse number in input"))
-                .enumerate()
-                .for_each(|(idx, num)| {
-                    // every new number of primary history can result in a new row of depth
-                    sequences.push(vec![]);
-                    for depth in 0..sequences.len() {
-                        if depth == 0 {
-                            sequences
-                                .get_mut(depth)
-                                .expect("top history expected")
-                                .push(num);
-                        } else {
-                            let len = sequences[depth].len();
-                            let above = &sequences[depth - 1];
-                            let left = *above.get(len).expect("expected value left");
-                            let right = *above.get(len + 1).expect("expected value right");
-                            sequences
-                                .get_mut(depth)
-                                .expect("seq current depth expect")
-                                .push(right - left);
-                        }
-                    }
-                });
-            sequences
-                .iter()
-                .rev()
-                .skip_while(|seq| seq.iter().all(|n| n == &0))
-                .fold(0, |acc, seq| {
-                    seq.first().expect("expected last value") - acc
-                })
-        })
-        .sum();
+    let answer = sum_extrapolated(&input, Direction::Backward)?;
 
     // output
     println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
@@ -113,7 +72,7 @@ async fn main() -> Result<()> {
 mod tests {
     use super::*;
 
-    static DATA: &'static str = "0 3 6 9 12 15
+    static DATA: &str = "0 3 6 9 12 15
 1 3 6 10 15 21
 10 13 16 21 30 45";
 
@@ -128,4 +87,10 @@ mod tests {
         assert_eq!(part2(DATA.to_owned())?, 2);
         Ok(())
     }
+
+    #[test]
+    fn test_bad_number_is_an_error() {
+        let err = part1("0 3 6\n1 x 3".to_owned()).unwrap_err();
+        assert!(err.to_string().starts_with("line 2: bad number \"x\""));
+    }
 }
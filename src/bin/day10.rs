@@ -39,8 +39,8 @@ impl PartialEq for Pipe {
 }
 
 impl Pipe {
-    fn for_char(c: char, position: (usize, usize)) -> Self {
-        match c {
+    fn for_char(c: char, position: (usize, usize)) -> Result<Self> {
+        Ok(match c {
             '|' => Self {
                 position,
                 direction: PipeDirection::Vertical,
@@ -73,8 +73,8 @@ impl Pipe {
                 position,
                 direction: PipeDirection::Start,
             },
-            _ => panic!("invalid pipe char"),
-        }
+            _ => return Err(format!("invalid pipe {c:?} at {position:?}").into()),
+        })
     }
 
     fn connects_to(&self) -> Vec<(usize, usize)> {
@@ -124,72 +124,148 @@ impl Pipes {
         for (row_index, line) in s.trim().lines().enumerate() {
             let mut row = vec![];
             for (col_index, char) in line.trim().chars().enumerate() {
-                row.push(Pipe::for_char(char, (row_index, col_index)));
+                row.push(Pipe::for_char(char, (row_index, col_index))?);
                 if char == 'S' {
                     start = Some((row_index, col_index))
                 }
             }
             grid.push(row);
         }
+        let position = start.ok_or("no start pipe")?;
 
-        // Get actual start pipe variant for easier code on the rest of the walking code
-        // later
-        let start = start
-            .map(|position| {
-                let mut up = true;
-                let mut left = true;
+        // Swap the start for the actual pipe shape so walking code never has to special case
+        // it. A side is connected when the neighbor over there points back at the start.
+        let points_back = |row_delta: i64, col_delta: i64| {
+            let row = position.0 as i64 + row_delta;
+            let col = position.1 as i64 + col_delta;
+            if row < 0 || col < 0 {
+                return false;
+            }
+            grid.get(row as usize)
+                .and_then(|r| r.get(col as usize))
+                .is_some_and(|neighbor| neighbor.connects_to().contains(&position))
+        };
+        let direction = match (
+            points_back(-1, 0),
+            points_back(1, 0),
+            points_back(0, -1),
+            points_back(0, 1),
+        ) {
+            (true, true, false, false) => PipeDirection::Vertical,
+            (false, false, true, true) => PipeDirection::Horizontal,
+            (true, false, false, true) => PipeDirection::NorthEast,
+            (true, false, true, false) => PipeDirection::NorthWest,
+            (false, true, true, false) => PipeDirection::SouthWest,
+            (false, true, false, true) => PipeDirection::SouthEast,
+            sides => {
+                return Err(format!(
+                    "start at {position:?} does not connect to exactly two pipes (up, down, left, right): {sides:?}"
+                )
+                .into())
+            }
+        };
+        let start = Pipe {
+            position,
+            direction,
+        };
+        grid[position.0][position.1] = start.clone();
+        Ok(Self { grid, start })
+    }
 
-                // We assume start always has two connections by definition of the problem. Will
-                // assume it is up and left and swap to the opposite after checking those.
-                let (row, col) = position;
-                if let Some(down) = grid.get(row + 1) {
-                    match down[col].direction {
-                        PipeDirection::Vertical
-                        | PipeDirection::NorthEast
-                        | PipeDirection::NorthWest => {
-                            up = false;
-                        }
-                        _ => {}
-                    }
-                }
-                if let Some(right) = grid[row].get(col + 1) {
-                    match right.direction {
-                        PipeDirection::Horizontal
-                        | PipeDirection::NorthWest
-                        | PipeDirection::SouthWest => {
-                            left = false;
-                        }
-                        _ => {}
-                    }
-                }
-                if up && left {
-                    Pipe {
-                        position,
-                        direction: PipeDirection::NorthWest,
-                    }
-                } else if up && !left {
-                    Pipe {
-                        position,
-                        direction: PipeDirection::NorthEast,
-                    }
-                } else if !up && left {
-                    Pipe {
-                        position,
-                        direction: PipeDirection::SouthWest,
-                    }
-                } else {
-                    Pipe {
-                        position,
-                        direction: PipeDirection::SouthEast,
-                    }
-                }
+    fn get(&self, position: &(usize, usize)) -> Option<&Pipe> {
+        self.grid.get(position.0)?.get(position.1)
+    }
+
+    /// Tiles of the main loop in walking order, starting at the start pipe. Errors when the
+    /// loop runs off the grid or into a tile that does not connect back.
+    fn loop_tiles(&self) -> Result<Vec<(usize, usize)>> {
+        let mut tiles = vec![self.start.position];
+        let mut previous = self.start.position;
+        let mut current = self.start.connects_to()[0];
+        while current != self.start.position {
+            let connects = self
+                .get(&current)
+                .ok_or_else(|| format!("loop runs off the grid at {current:?}"))?
+                .connects_to();
+            if !connects.contains(&previous) {
+                return Err(format!("loop is broken at {current:?}").into());
+            }
+            tiles.push(current);
+            let next = connects
+                .into_iter()
+                .find(|pos| *pos != previous)
+                .ok_or_else(|| format!("loop runs off the grid at {current:?}"))?;
+            previous = current;
+            current = next;
+        }
+        Ok(tiles)
+    }
+
+    /// Corners of the main loop in walking order, the polygon the loop traces out.
+    fn loop_vertices(&self) -> Result<Vec<(usize, usize)>> {
+        Ok(self
+            .loop_tiles()?
+            .into_iter()
+            .filter(|pos| {
+                self.get(pos).is_some_and(|pipe| {
+                    !matches!(
+                        pipe.direction,
+                        PipeDirection::Vertical | PipeDirection::Horizontal
+                    )
+                })
             })
-            .expect("start");
-        Ok(Self { grid, start })
+            .collect())
+    }
+
+    /// Tiles fully enclosed by the loop.
+    ///
+    /// Shoelace gives the area of the polygon through the tile centers and Pick's theorem
+    /// (`A = I + B/2 - 1`) turns that into the interior point count, where the boundary points
+    /// are exactly the loop tiles.
+    fn enclosed_count(&self) -> Result<usize> {
+        let vertices = self.loop_vertices()?;
+        let boundary = self.loop_tiles()?.len() as i64;
+        let twice_area = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(a, b)| a.0 as i64 * b.1 as i64 - b.0 as i64 * a.1 as i64)
+            .sum::<i64>()
+            .abs();
+        Ok(((twice_area - boundary) / 2 + 1) as usize)
     }
 
-    fn get(&self, position: &(usize, usize)) -> &Pipe {
-        &self.grid[position.0][position.1]
+    /// Draws the loop with box drawing characters, enclosed tiles as `█` and everything outside
+    /// as a space. Inside is found per row by counting how many loop pipes with a north end
+    /// were passed, so this is independent of the [`Self::enclosed_count`] math. Printed after
+    /// part 2 with `AOC_DEBUG=1`.
+    fn render(&self) -> Result<String> {
+        let on_loop: HashSet<_> = self.loop_tiles()?.into_iter().collect();
+        let mut out = String::new();
+        for row in self.grid.iter() {
+            let mut inside = false;
+            for pipe in row {
+                if on_loop.contains(&pipe.position) {
+                    if matches!(pipe.direction, PipeDirection::Vertical)
+                        || pipe.direction.is_north()
+                    {
+                        inside = !inside;
+                    }
+                    out.push(match pipe.direction {
+                        PipeDirection::Vertical => '│',
+                        PipeDirection::Horizontal => '─',
+                        PipeDirection::NorthEast => '└',
+                        PipeDirection::NorthWest => '┘',
+                        PipeDirection::SouthWest => '┐',
+                        PipeDirection::SouthEast => '┌',
+                        PipeDirection::Start | PipeDirection::Ground => '?',
+                    });
+                } else {
+                    out.push(if inside { '█' } else { ' ' });
+                }
+            }
+            out.push('\n');
+        }
+        Ok(out)
     }
 }
 
@@ -201,32 +277,7 @@ fn part1(input: String) -> Result<usize> {
 
     // algo
     let a_start = Instant::now();
-    let mut answer = 1;
-    let mut starters = pipes.start.connects_to().into_iter();
-    let mut paths: [(&Pipe, &Pipe); 2] = [
-        (&pipes.start, pipes.get(&starters.next().unwrap())),
-        (&pipes.start, pipes.get(&starters.next().unwrap())),
-    ];
-    loop {
-        // reached end
-        if paths[0].1.position.0 == paths[1].1.position.0
-            && paths[0].1.position.1 == paths[1].1.position.1
-        {
-            break;
-        }
-        answer += 1;
-        for path in paths.iter_mut() {
-            let next = path
-                .1
-                .connects_to()
-                .into_iter()
-                .filter(|pipe| pipe.0 != path.0.position.0 || pipe.1 != path.0.position.1)
-                .next()
-                .map(|pos| pipes.get(&pos))
-                .unwrap();
-            *path = (path.1, next);
-        }
-    }
+    let answer = pipes.loop_tiles()?.len() / 2;
     let algo_time = a_start.elapsed();
 
     // output
@@ -244,88 +295,11 @@ fn part2(input: String) -> Result<usize> {
 
     // algo
     let a_start = Instant::now();
-    let mut path = vec![
-        &pipes.start,
-        pipes
-            .start
-            .connects_to()
-            .into_iter()
-            .next()
-            .map(|pos| pipes.get(&pos))
-            .unwrap(),
-    ];
-    loop {
-        let previous = path.get(path.len() - 2).unwrap();
-        let next = path
-            .last()
-            .unwrap()
-            .connects_to()
-            .into_iter()
-            .filter(|pipe| pipe.0 != previous.position.0 || pipe.1 != previous.position.1)
-            .next()
-            .map(|pos| pipes.get(&pos))
-            .unwrap();
-        match next.direction {
-            PipeDirection::Start => {
-                break;
-            }
-            _ => {
-                path.push(next);
-            }
-        };
-    }
-    let mut answer = 0;
-    for (ri, row) in pipes.grid.iter().enumerate() {
-        for (idx, pipe) in row.iter().enumerate() {
-            if !path.contains(&pipe) {
-                // println!("Pipe check, {}, {}", ri, idx);
-                let (intersections, _) = row
-                    .iter()
-                    // Start at current non path pipe we are checking
-                    .skip(idx + 1)
-                    // Add fake pipe at the end so we can match on pipes on the right edge of the Grid
-                    // .chain(std::iter::once(&Pipe { position: (0, 0), direction:
-                    // PipeDirection::Ground, }))
-                    .fold(
-                        (0, None),
-                        |(i_count, intersecting_dir): (usize, Option<&PipeDirection>), pipe| {
-                            if path.contains(&pipe) {
-                                return match pipe.direction {
-                                    PipeDirection::Vertical => (i_count + 1, None),
-                                    PipeDirection::NorthWest
-                                    | PipeDirection::NorthEast
-                                    | PipeDirection::SouthEast
-                                    | PipeDirection::SouthWest => match intersecting_dir {
-                                        Some(last_direction) => {
-                                            match (
-                                                last_direction.is_north(),
-                                                pipe.direction.is_north(),
-                                            ) {
-                                                (true, true) | (false, false) => (i_count, None),
-                                                (true, false) | (false, true) => {
-                                                    (i_count + 1, None)
-                                                }
-                                            }
-                                        }
-                                        None => (i_count, Some(&pipe.direction)),
-                                    },
-                                    PipeDirection::Horizontal => (i_count, intersecting_dir),
-                                    _ => (i_count, None),
-                                };
-                            }
-                            return (i_count, None);
-                        },
-                    );
-
-                // println!( "\t\tPipe check, {}, {} intersections: {} | {}", ri, idx,
-                // intersections, (intersections > 0 && intersections % 2 == 1) );
-                if intersections > 0 && intersections % 2 == 1 {
-                    answer += 1;
-                }
-            }
-        }
-    }
+    let answer = pipes.enclosed_count()?;
     let algo_time = a_start.elapsed();
+    if get_config().debug {
+        println!("{}", pipes.render()?);
+    }
 
     // output
     println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
@@ -425,4 +399,39 @@ L7JLJL-JLJLJL--JLJ.L"
         );
         Ok(())
     }
+
+    #[test]
+    fn test_render_agrees_with_picks() -> Result<()> {
+        let pipes = Pipes::new(
+            "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+        )?;
+        assert!(matches!(pipes.start.direction, PipeDirection::SouthEast));
+        assert_eq!(pipes.loop_vertices()?.len(), 12);
+        let render = pipes.render()?;
+        assert_eq!(render.lines().nth(6), Some(" │██│ │██│ "));
+        assert_eq!(
+            render.chars().filter(|c| *c == '█').count(),
+            pipes.enclosed_count()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_broken_loops_are_errors() -> Result<()> {
+        let dead_end = Pipes::new("S-7\n|.|\nL-.")?;
+        assert!(dead_end.loop_tiles().is_err());
+        let off_grid = Pipes::new("S7\n||")?;
+        assert!(off_grid.loop_tiles().is_err());
+        assert!(part2("S7\n||".to_owned()).is_err());
+        assert!(Pipes::new("S-7\n|?|\nL-J").is_err());
+        Ok(())
+    }
 }
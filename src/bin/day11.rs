@@ -2,99 +2,95 @@ use aoc23::prelude::*;
 use derive_builder::Builder;
 use itertools::Itertools;
 use rayon::prelude::*;
-use std::{str::FromStr, time::Instant};
+use std::{ops::RangeInclusive, str::FromStr, time::Instant};
+use utils::sparse_grid::Pos;
 
 static DAY: u8 = 11;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-struct Vec2D {
-    x: usize,
-    y: usize,
-}
-
-impl From<(usize, usize)> for Vec2D {
-    fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 struct Space {
-    galaxies: Vec<Vec2D>,
-    x_max: usize,
-    y_max: usize,
+    galaxies: SparseGrid<()>,
 }
 
 impl Space {
     fn new(s: &str) -> Result<Self> {
-        let mut x_max = 0;
-        let mut y_max = 0;
-        let mut galaxies = vec![];
-        for (y, row) in s.trim().lines().enumerate() {
-            for (x, col) in row.chars().enumerate() {
-                if col == '#' {
-                    galaxies.push((x, y).into());
+        let mut galaxies = SparseGrid::new();
+        for (row, line) in s.trim().lines().enumerate() {
+            for (col, char) in line.chars().enumerate() {
+                if char == '#' {
+                    galaxies.insert((row as i64, col as i64), ());
                 }
-                x_max = x;
             }
-            y_max = y;
         }
-        Ok(Space {
-            galaxies,
-            x_max,
-            y_max,
-        })
+        Ok(Space { galaxies })
     }
 
-    fn expand(&mut self, by: usize) {
-        for y in (0..=self.y_max).rev() {
-            if self.galaxies.iter().filter(|g| g.y == y).count() == 0 {
-                self.galaxies
-                    .iter_mut()
-                    .filter(|g| g.y > y)
-                    .for_each(|g| g.y += by);
-            }
-        }
-        for x in (0..=self.x_max).rev() {
-            if self.galaxies.iter().filter(|g| g.x == x).count() == 0 {
-                self.galaxies
-                    .iter_mut()
-                    .filter(|g| g.x > x)
-                    .for_each(|g| g.x += by);
-            }
-        }
+    /// Galaxy positions after every empty row and col is replaced by `factor` copies of itself.
+    /// Empty lines come from the gaps between occupied ones, so this is O(galaxies) however
+    /// spread out they are.
+    fn expanded(&self, factor: usize) -> Vec<Pos> {
+        let grow = factor as i64 - 1;
+        let rows = gap_totals(self.galaxies.empty_rows());
+        let cols = gap_totals(self.galaxies.empty_cols());
+        self.galaxies
+            .positions()
+            .map(|&(row, col)| {
+                (
+                    row + empty_lines_before(&rows, row) * grow,
+                    col + empty_lines_before(&cols, col) * grow,
+                )
+            })
+            .collect()
     }
+}
 
-    fn galaxy_pairs(&self) -> Vec<(&Vec2D, &Vec2D)> {
-        let mut galaxy_pairs = vec![];
-        for i in 0..self.galaxies.len() {
-            for i2 in i + 1..self.galaxies.len() {
-                galaxy_pairs.push((&self.galaxies[i], &self.galaxies[i2]));
-            }
-        }
-        galaxy_pairs
+/// Last line of each gap with the running total of empty lines up to it.
+fn gap_totals(gaps: impl Iterator<Item = RangeInclusive<i64>>) -> Vec<(i64, i64)> {
+    gaps.scan(0, |total, gap| {
+        *total += gap.end() - gap.start() + 1;
+        Some((*gap.end(), *total))
+    })
+    .collect()
+}
+
+/// How many empty lines come before `line`, a binary search over [`gap_totals`].
+fn empty_lines_before(totals: &[(i64, i64)], line: i64) -> i64 {
+    match totals.partition_point(|(end, _)| *end < line) {
+        0 => 0,
+        gaps => totals[gaps - 1].1,
     }
 }
 
+/// Sum of manhattan distances between every pair of galaxies. Each axis is summed on its own
+/// after sorting: the i-th smallest value is the larger side of exactly i pairs.
+fn sum_of_pair_distances(galaxies: &[Pos]) -> usize {
+    let axis_sum = |values: Vec<i64>| -> i64 {
+        values
+            .into_iter()
+            .sorted_unstable()
+            .enumerate()
+            .fold((0, 0), |(total, seen_sum), (i, value)| {
+                (total + value * i as i64 - seen_sum, seen_sum + value)
+            })
+            .0
+    };
+    let rows = axis_sum(galaxies.iter().map(|g| g.0).collect());
+    let cols = axis_sum(galaxies.iter().map(|g| g.1).collect());
+    (rows + cols) as usize
+}
+
 fn part1(input: String) -> Result<usize> {
     // parse
     let start = Instant::now();
-    let mut space = Space::new(&input)?;
+    let space = Space::new(&input)?;
     let parsed_time = start.elapsed();
     let step_start = Instant::now();
-    space.expand(1);
+    let galaxies = space.expanded(2);
     let expansion_time = step_start.elapsed();
 
     // algo
     let step_start = Instant::now();
-    let mut answer = 0;
-    for pair in space.galaxy_pairs() {
-        let distance = pair.0.y.abs_diff(pair.1.y) + pair.0.x.abs_diff(pair.1.x);
-        answer += distance;
-    }
+    let answer = sum_of_pair_distances(&galaxies);
     let algo_time = step_start.elapsed();
 
     // output
@@ -105,22 +101,18 @@ fn part1(input: String) -> Result<usize> {
     Ok(answer)
 }
 
-fn part2(input: String) -> Result<usize> {
+fn part2(input: String, factor: usize) -> Result<usize> {
     // parse
     let start = Instant::now();
-    let mut space = Space::new(&input)?;
+    let space = Space::new(&input)?;
     let parsed_time = start.elapsed();
     let step_start = Instant::now();
-    space.expand(1000000 - 1);
+    let galaxies = space.expanded(factor);
     let expansion_time = step_start.elapsed();
 
     // algo
     let step_start = Instant::now();
-    let mut answer = 0;
-    for pair in space.galaxy_pairs() {
-        let distance = pair.0.y.abs_diff(pair.1.y) + pair.0.x.abs_diff(pair.1.x);
-        answer += distance;
-    }
+    let answer = sum_of_pair_distances(&galaxies);
     let algo_time = step_start.elapsed();
 
     // output
@@ -137,7 +129,7 @@ async fn main() -> Result<()> {
     println!("=====");
     let input = utils::aoc::get_puzzle_input(DAY).await?;
     part1(input.clone())?;
-    part2(input.clone())?;
+    part2(input.clone(), 1000000)?;
     Ok(())
 }
 
@@ -164,10 +156,21 @@ mod tests {
     }
 
     #[test]
-    // Must set the space.expand(1000000 - 1); to (100 - 1) instead for this test
-    #[ignore]
     fn test_part_2() -> Result<()> {
-        assert_eq!(part2(DATA.to_owned())?, 8410);
+        assert_eq!(part2(DATA.to_owned(), 10)?, 1030);
+        assert_eq!(part2(DATA.to_owned(), 100)?, 8410);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pair_distances_match_brute_force() -> Result<()> {
+        let galaxies = Space::new(DATA)?.expanded(2);
+        let brute_force: usize = galaxies
+            .iter()
+            .tuple_combinations()
+            .map(|(a, b)| (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as usize)
+            .sum();
+        assert_eq!(sum_of_pair_distances(&galaxies), brute_force);
         Ok(())
     }
 }
@@ -1,128 +1,214 @@
 use aoc23::prelude::*;
-use derive_builder::Builder;
 use itertools::Itertools;
 use rayon::prelude::*;
-use std::{collections::HashMap, str::FromStr, time::Instant};
+use std::time::Instant;
 
 static DAY: u8 = 12;
+/// Rows with more arrangements than this only show a sample of them in debug output.
+static DEBUG_ARRANGEMENTS: usize = 8;
 
-fn count_possibilities(
-    layout: &str,
-    contiguous: &[usize],
-    cache: &mut HashMap<(String, Vec<usize>), usize>,
-) -> usize {
-    if layout.chars().count() == 0 {
-        return if contiguous.len() == 0 {
-            // println!("\tLine is variant: {layout}");
-            1
-        } else {
-            0
-        };
+#[derive(Debug, Clone)]
+struct SpringRow {
+    springs: Vec<u8>,
+    groups: Vec<usize>,
+}
+
+impl SpringRow {
+    fn new(line: &str) -> Result<Self> {
+        let (springs, groups) = line
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("missing groups in {line:?}"))?;
+        if let Some(bad) = springs.bytes().find(|b| !matches!(b, b'.' | b'#' | b'?')) {
+            return Err(format!("unknown spring {:?} in {line:?}", bad as char).into());
+        }
+        Ok(Self {
+            springs: springs.as_bytes().to_vec(),
+            groups: groups
+                .split(',')
+                .map(|g| g.parse::<usize>())
+                .collect::<SResult<_, _>>()?,
+        })
     }
-    let cache_key = (layout.to_owned(), contiguous.to_vec());
-    if let Some(&result) = cache.get(&cache_key) {
-        return result;
+
+    fn parse_all(input: &str) -> Result<Vec<Self>> {
+        input.trim().lines().map(Self::new).collect()
+    }
+
+    /// Repeats the row `factor` times, springs joined by an extra `?`.
+    fn unfold(&self, factor: usize) -> Self {
+        Self {
+            springs: std::iter::repeat_n(&self.springs[..], factor)
+                .collect_vec()
+                .join(&b'?'),
+            groups: self.groups.repeat(factor),
+        }
+    }
+}
+
+/// DP over `(spring index, group index)` counting arrangements of `springs[i..]` that produce
+/// `groups[g..]`. The buffers are kept between rows so a thread only allocates when it meets a
+/// bigger row than it has seen.
+#[derive(Debug, Default)]
+struct Arrangements {
+    /// `(springs + 1) * (groups + 1)` cells, row major by spring index.
+    table: Vec<usize>,
+    /// `dots[i]` is how many `.` are in `springs[..i]`, used to check a group fits in O(1).
+    dots: Vec<usize>,
+    cols: usize,
+}
+
+impl Arrangements {
+    fn at(&self, i: usize, g: usize) -> usize {
+        self.table[i * self.cols + g]
     }
 
-    // Remove leading dots
-    let result = if layout.starts_with('.') {
-        count_possibilities(layout.to_owned().trim_matches('.'), contiguous, cache)
-        // Try both options for ?
-    } else if layout.starts_with('?') {
-        count_possibilities(&layout.to_owned().replacen('?', &".", 1), contiguous, cache)
-            + count_possibilities(&layout.to_owned().replacen('?', &"#", 1), contiguous, cache)
-        // If group, check if it matches
-    } else if layout.starts_with("#") {
-        // no groups left to match || not enough # to make the group || not all of this
-        // group are #
-        if contiguous.len() == 0
-            || layout.chars().count() < contiguous[0]
-            || layout.chars().take(contiguous[0]).any(|c| c == '.')
-        {
+    /// Ways to place `groups[g]` starting at spring `i`, given the rest of the table is filled.
+    fn placed(&self, row: &SpringRow, i: usize, g: usize) -> usize {
+        let (springs, n) = (&row.springs, row.springs.len());
+        if springs[i] == b'.' || g == row.groups.len() {
+            return 0;
+        }
+        let end = i + row.groups[g];
+        if end > n || self.dots[end] != self.dots[i] {
+            return 0;
+        }
+        if end == n {
+            self.at(n, g + 1)
+        } else if springs[end] == b'#' {
             0
-        } else if contiguous.len() > 1 {
-            if layout.len() < contiguous[0] + 1
-                || layout.chars().skip(contiguous[0]).next().unwrap() == '#'
-            {
-                0
+        } else {
+            self.at(end + 1, g + 1)
+        }
+    }
+
+    fn fill(&mut self, row: &SpringRow) {
+        let n = row.springs.len();
+        let groups = row.groups.len();
+        self.cols = groups + 1;
+        self.table.clear();
+        self.table.resize((n + 1) * self.cols, 0);
+        self.dots.clear();
+        self.dots.push(0);
+        for spring in row.springs.iter() {
+            let last = *self.dots.last().unwrap_or(&0);
+            self.dots.push(last + (*spring == b'.') as usize);
+        }
+
+        self.table[n * self.cols + groups] = 1;
+        for i in (0..n).rev() {
+            for g in 0..=groups {
+                let as_dot = if row.springs[i] != b'#' {
+                    self.at(i + 1, g)
+                } else {
+                    0
+                };
+                self.table[i * self.cols + g] = as_dot + self.placed(row, i, g);
+            }
+        }
+    }
+
+    fn count(&mut self, row: &SpringRow) -> usize {
+        self.fill(row);
+        self.at(0, 0)
+    }
+
+    /// The `k`th arrangement (in `.` before `#` order) with every `?` resolved.
+    fn nth(&mut self, row: &SpringRow, mut k: usize) -> Option<String> {
+        self.fill(row);
+        if k >= self.at(0, 0) {
+            return None;
+        }
+        let mut out = row.springs.clone();
+        let (mut i, mut g) = (0, 0);
+        while i < out.len() {
+            let as_dot = if row.springs[i] != b'#' {
+                self.at(i + 1, g)
             } else {
-                count_possibilities(
-                    &layout.chars().skip(contiguous[0] + 1).collect::<String>(),
-                    &contiguous[1..],
-                    cache,
-                )
+                0
+            };
+            if k < as_dot {
+                out[i] = b'.';
+                i += 1;
+                continue;
             }
+            k -= as_dot;
+            let end = i + row.groups[g];
+            out[i..end].fill(b'#');
+            if end < out.len() {
+                out[end] = b'.';
+            }
+            i = end + 1;
+            g += 1;
+        }
+        String::from_utf8(out).ok()
+    }
+
+    /// Every arrangement, only sensible for small rows.
+    fn all(&mut self, row: &SpringRow) -> Vec<String> {
+        let total = self.count(row);
+        (0..total).filter_map(|k| self.nth(row, k)).collect()
+    }
+
+    /// Up to `samples` arrangements spread evenly over all of them.
+    fn sample(&mut self, row: &SpringRow, samples: usize) -> Vec<String> {
+        let total = self.count(row);
+        let samples = samples.min(total);
+        (0..samples)
+            .filter_map(|s| self.nth(row, s * total / samples))
+            .collect()
+    }
+}
+
+fn count_unfolded(rows: &[SpringRow], factor: usize) -> usize {
+    rows.par_iter()
+        .map_init(Arrangements::default, |arrangements, row| {
+            arrangements.count(&row.unfold(factor))
+        })
+        .sum()
+}
+
+/// Prints each row with its arrangement count and its arrangements, all of them for small
+/// counts and an even sample otherwise.
+fn print_arrangements(rows: &[SpringRow]) {
+    let mut arrangements = Arrangements::default();
+    for row in rows {
+        let total = arrangements.count(row);
+        println!(
+            "{} {}: {total}",
+            String::from_utf8_lossy(&row.springs),
+            row.groups.iter().join(",")
+        );
+        let shown = if total <= DEBUG_ARRANGEMENTS {
+            arrangements.all(row)
         } else {
-            count_possibilities(
-                &layout.chars().skip(contiguous[0]).collect::<String>(),
-                &contiguous[1..],
-                cache,
-            )
+            arrangements.sample(row, DEBUG_ARRANGEMENTS)
+        };
+        for arrangement in shown {
+            println!("\t{arrangement}");
         }
-    } else {
-        panic!("Should not get here.")
-    };
-    cache.insert(cache_key, result);
-    result
+    }
 }
 
 fn part1(input: String) -> Result<usize> {
     // parse
     let start = Instant::now();
-    let answer = input
-        .trim()
-        .lines()
-        .enumerate()
-        .par_bridge()
-        .map(|(idx, line)| {
-            let mut split = line.trim().split_whitespace();
-            let layout = split.next().unwrap();
-            let contiguous = split
-                .next()
-                .unwrap()
-                .split(',')
-                .map(|s| s.parse::<usize>().unwrap())
-                .collect_vec();
-            let count = count_possibilities(&layout, &contiguous, &mut HashMap::new());
-
-            // println!("Line {idx}: {line} = {count}");
-            count
-        })
-        .sum();
+    let rows = SpringRow::parse_all(&input)?;
+    let answer = count_unfolded(&rows, 1);
 
     // output
     println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    if get_config().debug {
+        print_arrangements(&rows);
+    }
     Ok(answer)
 }
 
 fn part2(input: String) -> Result<usize> {
     // parse
     let start = Instant::now();
-    let answer = input
-        .trim()
-        .lines()
-        .enumerate()
-        .par_bridge()
-        .map(|(idx, line)| {
-            let mut split = line.trim().split_whitespace();
-            let layout = split.next().unwrap();
-            let contiguous = split
-                .next()
-                .unwrap()
-                .split(',')
-                .map(|s| s.parse::<usize>().unwrap())
-                .collect_vec();
-            let layout = std::iter::repeat(layout).take(5).join("?");
-            let contiguous = std::iter::repeat(contiguous)
-                .take(5)
-                .flatten()
-                .collect_vec();
-            let count = count_possibilities(&layout, &contiguous, &mut HashMap::new());
-
-            // println!("Line {idx}: {line} = {count}");
-            count
-        })
-        .sum();
+    let rows = SpringRow::parse_all(&input)?;
+    let answer = count_unfolded(&rows, 5);
 
     // output
     println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
@@ -143,7 +229,7 @@ async fn main() -> Result<()> {
 mod tests {
     use super::*;
 
-    static DATA: &'static str = "???.### 1,1,3
+    static DATA: &str = "???.### 1,1,3
 .??..??...?##. 1,1,3
 ?#?#?#?#?#?#?#? 1,3,1,6
 ????.#...#... 4,1,1
@@ -161,4 +247,25 @@ mod tests {
         assert_eq!(part2(DATA.to_owned())?, 525152);
         Ok(())
     }
+
+    #[test]
+    fn test_arrangements_match_count() -> Result<()> {
+        let row = SpringRow::new("?###???????? 3,2,1")?;
+        let mut arrangements = Arrangements::default();
+        let all = arrangements.all(&row);
+        assert_eq!(all.len(), 10);
+        assert_eq!(all[0], ".###....##.#");
+        assert_eq!(all.iter().unique().count(), 10);
+        for arrangement in all.iter() {
+            let groups = arrangement
+                .split('.')
+                .filter(|g| !g.is_empty())
+                .map(str::len)
+                .collect_vec();
+            assert_eq!(groups, row.groups);
+        }
+        assert_eq!(arrangements.sample(&row, 3).len(), 3);
+        assert_eq!(count_unfolded(&[row], 2), 150);
+        Ok(())
+    }
 }
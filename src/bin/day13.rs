@@ -6,140 +6,123 @@ use std::{fmt::Debug, str::FromStr, time::Instant};
 
 static DAY: u8 = 13;
 
+/// Where a pattern reflects, counted the way the puzzle scores it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reflection {
+    /// Mirror line between rows, with this many rows above it.
+    Horizontal(usize),
+    /// Mirror line between cols, with this many cols left of it.
+    Vertical(usize),
+}
+
+impl Reflection {
+    fn summary(&self) -> usize {
+        match self {
+            Reflection::Horizontal(above) => above * 100,
+            Reflection::Vertical(left) => *left,
+        }
+    }
+}
+
+/// Rock pattern with every row and every col packed into a bitmask (`#` = 1), so comparing two
+/// lines is one xor and counting differences is a popcount.
 #[derive(Clone)]
-struct Grid {
-    cells: Vec<Vec<bool>>,
+struct Pattern {
+    rows: Vec<u64>,
+    cols: Vec<u64>,
 }
 
-impl Debug for Grid {
+impl Debug for Pattern {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in &self.cells {
-            f.write_str("\t");
-            for b in row {
-                f.write_str(if *b { "#" } else { "." });
+        for row in &self.rows {
+            f.write_str("\t")?;
+            for col in 0..self.cols.len() {
+                f.write_str(if row & (1 << col) != 0 { "#" } else { "." })?;
             }
-            f.write_str("\n");
+            f.write_str("\n")?;
         }
         Ok(())
     }
 }
 
-type Split<'a> = (&'a [Vec<bool>], &'a [Vec<bool>]);
-
-impl Grid {
+impl Pattern {
     fn new(s: &str) -> Result<Self> {
-        Ok(Self {
-            cells: s
-                .trim()
-                .lines()
-                .map(|l| l.chars().map(|c| c == '#').collect_vec())
-                .collect(),
-        })
-    }
-
-    fn all_vertical_splits(&self) -> Vec<Split> {
-        let mut splits = vec![];
-        for i in 1..self.cells.len() {
-            splits.push(self.vertical_split(i));
+        let lines = s.trim().lines().map(str::trim).collect_vec();
+        let width = lines.first().map(|l| l.len()).unwrap_or(0);
+        if width > 64 || lines.len() > 64 {
+            return Err(format!(
+                "pattern {}x{} does not fit in u64 masks",
+                lines.len(),
+                width
+            )
+            .into());
         }
-        splits
-    }
-
-    fn vertical_split(&self, index: usize) -> Split {
-        (&self.cells[0..index], &self.cells[index..])
-    }
-
-    fn is_vertical_mirror(top: &[Vec<bool>], bot: &[Vec<bool>]) -> bool {
-        let tl = top.len();
-        let bl = bot.len();
-        for i in 0..usize::min(tl, bl) {
-            let t = &top[tl - 1 - i];
-            let b = &bot[i];
-
-            // println!("\tChecking {i}: {t:?} <=> {b:?} || {}", t == b);
-            if t != b {
-                return false;
+        let mut rows = vec![0u64; lines.len()];
+        let mut cols = vec![0u64; width];
+        for (r, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                return Err(
+                    format!("pattern row {r} is {} wide, expected {width}", line.len()).into(),
+                );
+            }
+            for (c, char) in line.chars().enumerate() {
+                match char {
+                    '#' => {
+                        rows[r] |= 1 << c;
+                        cols[c] |= 1 << r;
+                    }
+                    '.' => {}
+                    unknown => return Err(format!("unknown pattern tile {unknown:?}").into()),
+                }
             }
         }
-        true
+        Ok(Self { rows, cols })
+    }
+
+    fn parse_all(input: &str) -> Result<Vec<Self>> {
+        input.trim().split("\n\n").map(Self::new).collect()
     }
 
-    fn is_vertical_mirror_with_smudge(top: &[Vec<bool>], bot: &[Vec<bool>]) -> bool {
-        let tl = top.len();
-        let bl = bot.len();
-        let mut claimed_smudge = false;
-        for i in 0..usize::min(tl, bl) {
-            let t = &top[tl - 1 - i];
-            let b = &bot[i];
-
-            // println!( "\tChecking {i}: {t:?} <=> {b:?} || {} || {}", t == b, claimed_smudge
-            // );
-            if t != b {
-                if !claimed_smudge && t.iter().zip(b.iter()).filter(|(z, x)| z != x).count() == 1 {
-                    claimed_smudge = true
-                } else {
+    /// Every split index in `lines` where the mirrored pairs differ in exactly `smudges` cells.
+    fn mirror_splits(lines: &[u64], smudges: u32) -> impl Iterator<Item = usize> + '_ {
+        (1..lines.len()).filter(move |&split| {
+            let mut differences = 0;
+            for (before, after) in lines[..split].iter().rev().zip(&lines[split..]) {
+                differences += (before ^ after).count_ones();
+                if differences > smudges {
                     return false;
                 }
             }
-        }
-
-        // FFS I had `true` here but all mirrors have EXACTLY ONE smudge, not at most
-        // one...
-        claimed_smudge
+            differences == smudges
+        })
     }
 
-    fn clone_and_rotate(&self) -> Self {
-        Self {
-            cells: (0..self.cells[0].len())
-                .map(|i| self.cells.iter().map(|row| row[i]).collect())
-                .collect(),
-        }
+    /// All reflection lines in both axes that need exactly `smudges` cells fixed.
+    fn reflections(&self, smudges: u32) -> Vec<Reflection> {
+        Self::mirror_splits(&self.rows, smudges)
+            .map(Reflection::Horizontal)
+            .chain(Self::mirror_splits(&self.cols, smudges).map(Reflection::Vertical))
+            .collect()
     }
 }
 
+fn summarize(patterns: &[Pattern], smudges: u32) -> usize {
+    patterns
+        .iter()
+        .flat_map(|pattern| pattern.reflections(smudges))
+        .map(|reflection| reflection.summary())
+        .sum()
+}
+
 fn part1(input: String) -> Result<usize> {
     // parse
     let start = Instant::now();
-    let grids = input
-        .split("\n\n")
-        .map(|g| Grid::new(g).unwrap())
-        .collect_vec();
+    let patterns = Pattern::parse_all(&input)?;
     let parsed_time = start.elapsed();
 
     // algo
     let a_start = Instant::now();
-    let answer: usize = grids
-        .iter()
-        .map(|grid| {
-            let mut vertical_mirror_at = None;
-            for (idx, (top, bot)) in grid.all_vertical_splits().iter().enumerate() {
-                if Grid::is_vertical_mirror(top, bot) {
-                    vertical_mirror_at = Some(idx);
-                    break;
-                }
-            }
-            let mut horizontal_mirror_at = None;
-            if vertical_mirror_at.is_none() {
-                let rotated = grid.clone_and_rotate();
-                for (idx, (top, bot)) in rotated.all_vertical_splits().iter().enumerate() {
-                    if Grid::is_vertical_mirror(top, bot) {
-                        horizontal_mirror_at = Some(idx);
-                        break;
-                    }
-                }
-            }
-
-            // index + 1 = number of rows to the left or above the mirror index
-            let value = horizontal_mirror_at
-                .map(|h| h + 1)
-                .or(vertical_mirror_at.map(|v| (v + 1) * 100))
-                .unwrap_or(0);
-
-            // println!( "Grid: vertical mirror at: {vertical_mirror_at:?}\thorizontal at:
-            // {horizontal_mirror_at:?} == {value}" );
-            value
-        })
-        .sum();
+    let answer = summarize(&patterns, 0);
     let algo_time = a_start.elapsed();
 
     // output
@@ -152,54 +135,16 @@ fn part1(input: String) -> Result<usize> {
 fn part2(input: String) -> Result<usize> {
     // parse
     let start = Instant::now();
-    let grids = input
-        .split("\n\n")
-        .map(|g| Grid::new(g).unwrap())
-        .collect_vec();
+    let patterns = Pattern::parse_all(&input)?;
     let parsed_time = start.elapsed();
 
     // algo
     let a_start = Instant::now();
-    let answer: usize = grids
-        .iter()
-        .map(|grid| {
-            // println!("GRID: {grid:?}");
-            let mut vertical_mirror_at = None;
-            for (idx, (top, bot)) in grid.all_vertical_splits().iter().enumerate() {
-                if Grid::is_vertical_mirror_with_smudge(top, bot) {
-                    // println!("GOT VERT: {idx}");
-                    vertical_mirror_at = Some(idx);
-                    break;
-                }
-            }
-            let mut horizontal_mirror_at = None;
-            if vertical_mirror_at.is_none() {
-                let rotated = grid.clone_and_rotate();
-
-                // println!("ROTATED: {grid:?}");
-                for (idx, (top, bot)) in rotated.all_vertical_splits().iter().enumerate() {
-                    if Grid::is_vertical_mirror_with_smudge(top, bot) {
-                        horizontal_mirror_at = Some(idx);
-                        break;
-                    }
-                }
-            }
-
-            // index + 1 = number of rows to the left or above the mirror index
-            let value = horizontal_mirror_at
-                .map(|h| h + 1)
-                .or(vertical_mirror_at.map(|v| (v + 1) * 100))
-                .unwrap_or(0);
-
-            // println!( "Grid: vertical mirror at: {vertical_mirror_at:?}\thorizontal at:
-            // {horizontal_mirror_at:?} == {value}" );
-            value
-        })
-        .sum();
+    let answer = summarize(&patterns, 1);
     let algo_time = a_start.elapsed();
 
     // output
-    println!("part 2: {answer} == 31836\t[total: {:?}]", start.elapsed());
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
     println!("\tparse: {parsed_time:?}");
     println!("\talgo: {algo_time:?}");
     Ok(answer)
@@ -246,4 +191,14 @@ mod tests {
         assert_eq!(part2(DATA.to_owned())?, 400);
         Ok(())
     }
+
+    #[test]
+    fn test_reflections_by_smudge_count() -> Result<()> {
+        let patterns = Pattern::parse_all(DATA)?;
+        assert_eq!(patterns[0].reflections(0), vec![Reflection::Vertical(5)]);
+        assert_eq!(patterns[1].reflections(0), vec![Reflection::Horizontal(4)]);
+        assert_eq!(patterns[0].reflections(1), vec![Reflection::Horizontal(3)]);
+        assert_eq!(patterns[1].reflections(1), vec![Reflection::Horizontal(1)]);
+        Ok(())
+    }
 }
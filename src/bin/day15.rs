@@ -1,8 +1,7 @@
 use aoc23::prelude::*;
-use derive_builder::Builder;
 use itertools::Itertools;
 use rayon::prelude::*;
-use std::{str::FromStr, time::Instant};
+use std::{fmt::Display, time::Instant};
 
 static DAY: u8 = 15;
 
@@ -23,13 +22,19 @@ impl Seq {
             let mut value = acc;
             value += *char as usize;
             value *= 17;
-            value = value % 256;
+            value %= 256;
             value
         })
     }
 }
 
-#[derive(Debug, Clone)]
+impl Display for Seq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.chars.iter().try_for_each(|c| write!(f, "{c}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Operation {
     Remove,
     // focal length
@@ -44,32 +49,144 @@ struct LensOp {
 }
 
 impl LensOp {
-    fn new(value: &Seq) -> Self {
+    fn new(value: &Seq) -> Result<Self> {
+        let step: String = value.chars.iter().collect();
+        let split = value
+            .chars
+            .iter()
+            .position(|c| matches!(c, '=' | '-'))
+            .ok_or_else(|| format!("no operation in step {step:?}"))?;
+        if split == 0 {
+            return Err(format!("no label in step {step:?}").into());
+        }
         let label = Seq {
-            chars: value
-                .chars
-                .iter()
-                .take_while(|c| c != &&'=' && c != &&'-')
-                .map(|c| *c)
-                .collect(),
+            chars: value.chars[..split].to_vec(),
         };
-        let box_index = label.hash();
-        let mut op = value.chars.iter().skip(label.chars.len());
-        Self {
+        let rest: String = value.chars[split + 1..].iter().collect();
+        let operation = match value.chars[split] {
+            '-' if rest.is_empty() => Operation::Remove,
+            '-' => return Err(format!("unexpected {rest:?} after removal in {step:?}").into()),
+            _ => Operation::Insert(
+                rest.parse()
+                    .map_err(|e| format!("bad focal length in {step:?}: {e}"))?,
+            ),
+        };
+        Ok(Self {
+            box_index: label.hash(),
             label,
-            box_index,
-            operation: match op.next().unwrap() {
-                '-' => Operation::Remove,
-                '=' => Operation::Insert(op.next().unwrap().to_digit(10).unwrap() as usize),
-                o => panic!("Unknown op {o}"),
-            },
+            operation,
+        })
+    }
+
+    fn parse_all(input: &str) -> Result<Vec<Self>> {
+        input
+            .split(',')
+            .map(|step| Self::new(&Seq::new(step)?))
+            .collect()
+    }
+}
+
+impl Display for LensOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)?;
+        match self.operation {
+            Operation::Remove => write!(f, "-"),
+            Operation::Insert(focal_length) => write!(f, "={focal_length}"),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Lens {
+    label: Seq,
+    focal_length: usize,
+}
+
 #[derive(Debug, Clone, Default)]
 struct LensBox {
-    lenses: Vec<LensOp>,
+    lenses: Vec<Lens>,
+}
+
+/// The 256 boxes of the HASHMAP, each holding its lenses front to back.
+#[derive(Debug, Clone)]
+struct LensBoxes {
+    boxes: Vec<LensBox>,
+}
+
+impl Default for LensBoxes {
+    fn default() -> Self {
+        Self {
+            boxes: vec![LensBox::default(); 256],
+        }
+    }
+}
+
+impl LensBoxes {
+    fn apply(&mut self, lens_op: &LensOp) {
+        let lenses = &mut self.boxes[lens_op.box_index].lenses;
+        let existing = lenses.iter().position(|lens| lens.label == lens_op.label);
+        match (&lens_op.operation, existing) {
+            (Operation::Remove, Some(idx)) => {
+                lenses.remove(idx);
+            }
+            (Operation::Remove, None) => {}
+            (Operation::Insert(focal_length), Some(idx)) => {
+                lenses[idx].focal_length = *focal_length;
+            }
+            (Operation::Insert(focal_length), None) => lenses.push(Lens {
+                label: lens_op.label.clone(),
+                focal_length: *focal_length,
+            }),
+        }
+    }
+
+    /// Every lens with its box index and slot, boxes in order and front to back within a box.
+    fn iter(&self) -> impl Iterator<Item = (usize, usize, &Lens)> {
+        self.boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(box_idx, lens_box)| {
+                lens_box
+                    .lenses
+                    .iter()
+                    .enumerate()
+                    .map(move |(slot, lens)| (box_idx, slot, lens))
+            })
+    }
+
+    fn focusing_power(&self) -> usize {
+        self.iter()
+            .map(|(box_idx, slot, lens)| (box_idx + 1) * (slot + 1) * lens.focal_length)
+            .sum()
+    }
+
+    /// Applies every op, recording the non-empty boxes after each step like the puzzle shows.
+    /// Printed after part 2 with `AOC_DEBUG=1`.
+    fn trace(&mut self, lens_ops: &[LensOp]) -> String {
+        lens_ops
+            .iter()
+            .map(|lens_op| {
+                self.apply(lens_op);
+                format!("After \"{lens_op}\":\n{self}")
+            })
+            .join("\n")
+    }
+}
+
+impl Display for LensBoxes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (box_idx, lens_box) in self.boxes.iter().enumerate() {
+            if lens_box.lenses.is_empty() {
+                continue;
+            }
+            write!(f, "Box {box_idx}:")?;
+            for lens in lens_box.lenses.iter() {
+                write!(f, " [{} {}]", lens.label, lens.focal_length)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
 
 fn part1(input: String) -> Result<usize> {
@@ -93,60 +210,25 @@ fn part1(input: String) -> Result<usize> {
 fn part2(input: String) -> Result<usize> {
     // parse
     let start = Instant::now();
-    let lens_ops: Vec<LensOp> = input
-        .split(',')
-        .map(|seq| Seq::new(seq).unwrap())
-        .map(|s| LensOp::new(&s))
-        .collect();
-    let mut lens_boxes: Vec<LensBox> = std::iter::repeat_with(|| LensBox::default())
-        .take(256)
-        .collect();
+    let lens_ops = LensOp::parse_all(&input)?;
     let parsed_time = start.elapsed();
 
     // algo
     let a_start = Instant::now();
-    for lens_op in lens_ops {
-        if let Some(lens_box) = lens_boxes.get_mut(lens_op.box_index) {
-            match lens_op.operation {
-                Operation::Remove => lens_box.lenses.retain(|lop| lop.label != lens_op.label),
-                Operation::Insert(_) => {
-                    if let Some(existing) = lens_box
-                        .lenses
-                        .iter_mut()
-                        .find(|lop| lop.label == lens_op.label)
-                    {
-                        *existing = lens_op;
-                    } else {
-                        lens_box.lenses.push(lens_op);
-                    }
-                }
-            }
-        }
-    }
-    let answer = lens_boxes
+    let mut lens_boxes = LensBoxes::default();
+    lens_ops
         .iter()
-        .enumerate()
-        .map(|(box_idx, lens_box)| {
-            lens_box
-                .lenses
-                .iter()
-                .enumerate()
-                .map(|(lens_idx, lens)| {
-                    if let Operation::Insert(focal_length) = lens.operation {
-                        (box_idx + 1) * (lens_idx + 1) * focal_length
-                    } else {
-                        panic!("How did a removal lens get in there?");
-                    }
-                })
-                .sum::<usize>()
-        })
-        .sum();
+        .for_each(|lens_op| lens_boxes.apply(lens_op));
+    let answer = lens_boxes.focusing_power();
     let algo_time = a_start.elapsed();
 
     // output
     println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
     println!("\tparse: {parsed_time:?}");
     println!("\talgo: {algo_time:?}");
+    if get_config().debug {
+        println!("{}", LensBoxes::default().trace(&lens_ops));
+    }
     Ok(answer)
 }
 
@@ -187,4 +269,29 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_trace_matches_puzzle() -> Result<()> {
+        let lens_ops = LensOp::parse_all("rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7")?;
+        let mut lens_boxes = LensBoxes::default();
+        let trace = lens_boxes.trace(&lens_ops);
+        assert!(trace.starts_with("After \"rn=1\":\nBox 0: [rn 1]\n\nAfter \"cm-\":\n"));
+        assert!(
+            trace.ends_with("After \"ot=7\":\nBox 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n")
+        );
+        assert_eq!(lens_boxes.focusing_power(), 145);
+        assert_eq!(lens_boxes.iter().count(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lens_op_parsing() -> Result<()> {
+        let lens_op = LensOp::new(&Seq::new("abc=12")?)?;
+        assert_eq!(lens_op.operation, Operation::Insert(12));
+        assert_eq!(lens_op.to_string(), "abc=12");
+        for bad in ["abc", "=3", "abc=", "abc=x", "abc-1"] {
+            assert!(LensOp::new(&Seq::new(bad)?).is_err(), "{bad}");
+        }
+        Ok(())
+    }
 }
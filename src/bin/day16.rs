@@ -2,8 +2,9 @@ use aoc23::prelude::*;
 use derive_builder::Builder;
 use grid::Grid;
 use itertools::Itertools;
+use pathfinding::directed::strongly_connected_components::strongly_connected_components;
 use rayon::prelude::*;
-use std::{collections::HashSet, ops::Add, str::FromStr, time::Instant};
+use std::{collections::HashMap, ops::Add, rc::Rc, str::FromStr, time::Instant};
 
 static DAY: u8 = 16;
 
@@ -93,36 +94,15 @@ impl Beam {
         )
     }
 
-    fn next_path(&self, tiles: &Grid<Tile>) -> Vec<Beam> {
-        tiles
-            .get(self.0, self.1)
-            .map(|tile| tile.direction_to(&self.2))
-            .map(|dirs| {
-                dirs.iter()
-                    .map(|dir| self.add_dir(dir))
-                    .filter(|beam| tiles.get(beam.0, beam.1).is_some())
-                    .collect_vec()
-            })
-            .unwrap_or(vec![])
-    }
-
-    fn part2_starts(grid: &Grid<Tile>) -> Vec<Self> {
+    /// Every beam entering from the edge: each row from both sides and each col from both ends.
+    fn edge_starts(grid: &Grid<Tile>) -> Vec<Self> {
+        let (rows, cols) = grid.size();
         let mut starts = vec![];
-        let rows = grid.rows();
-        let cols = grid.cols();
-        starts.push(Beam(0, 0, Dir::Right));
-        starts.push(Beam(0, 0, Dir::Down));
-        starts.push(Beam(0, cols - 1, Dir::Down));
-        starts.push(Beam(0, cols - 1, Dir::Left));
-        starts.push(Beam(rows - 1, cols - 1, Dir::Left));
-        starts.push(Beam(rows - 1, cols - 1, Dir::Up));
-        starts.push(Beam(rows - 1, 0, Dir::Up));
-        starts.push(Beam(rows - 1, 0, Dir::Right));
-        for row in 1..rows - 2 {
+        for row in 0..rows {
             starts.push(Beam(row, 0, Dir::Right));
             starts.push(Beam(row, cols - 1, Dir::Left));
         }
-        for col in 1..cols - 2 {
+        for col in 0..cols {
             starts.push(Beam(0, col, Dir::Down));
             starts.push(Beam(rows - 1, col, Dir::Up));
         }
@@ -130,6 +110,213 @@ impl Beam {
     }
 }
 
+/// One bit per tile, row major.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Energized(Vec<u64>);
+
+impl Energized {
+    fn new(tiles: usize) -> Self {
+        Self(vec![0; tiles.div_ceil(64)])
+    }
+
+    fn insert(&mut self, idx: usize) {
+        self.0[idx / 64] |= 1 << (idx % 64);
+    }
+
+    fn contains(&self, idx: usize) -> bool {
+        self.0[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a |= b);
+    }
+
+    fn count(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// Nodes of a DAG ordered so that every node comes after all of its successors.
+fn sinks_first(successors: &[Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(successors.len());
+    let mut visited = vec![false; successors.len()];
+    for root in 0..successors.len() {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.pop() {
+            match successors[node].get(next) {
+                Some(&child) => {
+                    stack.push((node, next + 1));
+                    if !visited[child] {
+                        visited[child] = true;
+                        stack.push((child, 0));
+                    }
+                }
+                None => order.push(node),
+            }
+        }
+    }
+    order
+}
+
+/// Beam paths compressed into a graph. A node is a beam entering a tile that turns or splits it,
+/// an edge is the straight run of tiles the beam crosses until the next such tile (or the edge
+/// of the grid).
+///
+/// Nodes are grouped into strongly connected components, since every node in a cycle energizes
+/// the same tiles, and each component's energized tiles are computed once from the components
+/// it leads to. Any start is then one straight walk plus a lookup.
+struct BeamGraph<'a> {
+    grid: &'a Grid<Tile>,
+    /// Energized tiles reachable from each node, shared between nodes of one component.
+    energized: Vec<Rc<Energized>>,
+    node_index: HashMap<Beam, usize>,
+}
+
+impl<'a> BeamGraph<'a> {
+    fn tile_index(&self, row: usize, col: usize) -> usize {
+        row * self.grid.cols() + col
+    }
+
+    fn turns(tile: &Tile, dir: &Dir) -> bool {
+        tile.direction_to(dir) != [dir.clone()]
+    }
+
+    fn step(&self, row: usize, col: usize, dir: &Dir) -> Option<(usize, usize)> {
+        let next = Beam(row, col, dir.clone()).add_dir(dir);
+        (self.grid.get(next.0, next.1).is_some() && (next.0, next.1) != (row, col))
+            .then_some((next.0, next.1))
+    }
+
+    /// Walks straight from `(row, col)` (inclusive) in `dir` until a tile turns the beam.
+    /// Returns the tiles crossed before it and the node for that tile, if the beam did not
+    /// leave the grid.
+    fn walk(&self, row: usize, col: usize, dir: &Dir) -> (Vec<usize>, Option<usize>) {
+        let mut crossed = vec![];
+        let mut pos = Some((row, col));
+        while let Some((r, c)) = pos {
+            if let Some(node) = self.node_index.get(&Beam(r, c, dir.clone())) {
+                return (crossed, Some(*node));
+            }
+            crossed.push(self.tile_index(r, c));
+            pos = self.step(r, c, dir);
+        }
+        (crossed, None)
+    }
+
+    fn new(grid: &'a Grid<Tile>) -> Self {
+        let mut graph = Self {
+            grid,
+            energized: vec![],
+            node_index: HashMap::new(),
+        };
+        let mut nodes = vec![];
+        for ((row, col), tile) in grid.indexed_iter() {
+            for dir in [Dir::Up, Dir::Right, Dir::Down, Dir::Left] {
+                if Self::turns(tile, &dir) {
+                    graph
+                        .node_index
+                        .insert(Beam(row, col, dir.clone()), nodes.len());
+                    nodes.push(Beam(row, col, dir));
+                }
+            }
+        }
+
+        // Tiles each node lights up itself plus the nodes its straight runs lead to.
+        let mut own_tiles = vec![];
+        let mut successors = vec![];
+        for Beam(row, col, dir) in nodes.iter() {
+            let mut tiles = vec![graph.tile_index(*row, *col)];
+            let mut next = vec![];
+            for out in grid[(*row, *col)].direction_to(dir) {
+                if let Some((r, c)) = graph.step(*row, *col, &out) {
+                    let (crossed, node) = graph.walk(r, c, &out);
+                    tiles.extend(crossed);
+                    next.extend(node);
+                }
+            }
+            own_tiles.push(tiles);
+            successors.push(next);
+        }
+
+        // Cycles share one energized set. `pathfinding` does not promise any order for the
+        // components, so the condensed graph is ordered sinks first here before combining.
+        let components = strongly_connected_components(&(0..nodes.len()).collect_vec(), |n| {
+            successors[*n].clone()
+        });
+        let mut component_of = vec![0; nodes.len()];
+        for (component_idx, component) in components.iter().enumerate() {
+            for node in component {
+                component_of[*node] = component_idx;
+            }
+        }
+        let component_successors = components
+            .iter()
+            .enumerate()
+            .map(|(component_idx, component)| {
+                component
+                    .iter()
+                    .flat_map(|node| successors[*node].iter().map(|next| component_of[*next]))
+                    .filter(|next| *next != component_idx)
+                    .sorted()
+                    .dedup()
+                    .collect_vec()
+            })
+            .collect_vec();
+        let mut energized: Vec<Option<Rc<Energized>>> = vec![None; components.len()];
+        for component_idx in sinks_first(&component_successors) {
+            let mut lit = Energized::new(grid.rows() * grid.cols());
+            for node in components[component_idx].iter() {
+                own_tiles[*node].iter().for_each(|t| lit.insert(*t));
+            }
+            for next in component_successors[component_idx].iter() {
+                let next_lit = energized[*next]
+                    .as_ref()
+                    .expect("successor components are combined first");
+                lit.union_with(next_lit);
+            }
+            energized[component_idx] = Some(Rc::new(lit));
+        }
+        graph.energized = (0..nodes.len())
+            .map(|n| {
+                energized[component_of[n]]
+                    .clone()
+                    .expect("every component is combined")
+            })
+            .collect();
+        graph
+    }
+
+    fn energized_from(&self, start: &Beam) -> Energized {
+        let (crossed, node) = self.walk(start.0, start.1, &start.2);
+        let mut lit = match node {
+            Some(node) => (*self.energized[node]).clone(),
+            None => Energized::new(self.grid.rows() * self.grid.cols()),
+        };
+        crossed.into_iter().for_each(|t| lit.insert(t));
+        lit
+    }
+
+    /// Energized map in the puzzle's `#` / `.` format, printed for part 1 with `AOC_DEBUG=1`.
+    fn render(&self, energized: &Energized) -> String {
+        let mut out = String::new();
+        for row in 0..self.grid.rows() {
+            for col in 0..self.grid.cols() {
+                out.push(if energized.contains(self.tile_index(row, col)) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
 fn part1(input: String) -> Result<usize> {
     // parse
     let start = Instant::now();
@@ -138,26 +325,18 @@ fn part1(input: String) -> Result<usize> {
 
     // algo
     let a_start = Instant::now();
-    let mut energized = HashSet::new();
-    let mut full_beam = HashSet::new();
-    let mut beam_path = vec![Beam(0, 0, Dir::Right)];
-    while let Some(beam) = beam_path.pop() {
-        energized.insert((beam.0, beam.1));
-        full_beam.insert(beam.clone());
-        beam.next_path(&grid)
-            .into_iter()
-            .filter(|b| !full_beam.contains(b))
-            .for_each(|new_beam| {
-                beam_path.push(new_beam);
-            });
-    }
-    let answer = energized.len();
+    let graph = BeamGraph::new(&grid);
+    let energized = graph.energized_from(&Beam(0, 0, Dir::Right));
+    let answer = energized.count();
     let algo_time = a_start.elapsed();
 
     // output
     println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
     println!("\tparse: {parsed_time:?}");
     println!("\talgo: {algo_time:?}");
+    if get_config().debug {
+        print!("{}", graph.render(&energized));
+    }
     Ok(answer)
 }
 
@@ -169,26 +348,12 @@ fn part2(input: String) -> Result<usize> {
 
     // algo
     let a_start = Instant::now();
-    let answer = Beam::part2_starts(&grid)
-        .into_iter()
-        .map(|start| {
-            let mut energized = HashSet::new();
-            let mut full_beam = HashSet::new();
-            let mut beam_path = vec![start];
-            while let Some(beam) = beam_path.pop() {
-                energized.insert((beam.0, beam.1));
-                full_beam.insert(beam.clone());
-                beam.next_path(&grid)
-                    .into_iter()
-                    .filter(|b| !full_beam.contains(b))
-                    .for_each(|new_beam| {
-                        beam_path.push(new_beam);
-                    });
-            }
-            energized.len()
-        })
+    let graph = BeamGraph::new(&grid);
+    let answer = Beam::edge_starts(&grid)
+        .iter()
+        .map(|start| graph.energized_from(start).count())
         .max()
-        .unwrap();
+        .unwrap_or(0);
     let algo_time = a_start.elapsed();
 
     // output
@@ -211,12 +376,9 @@ async fn main() -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        assert_eq!(
-            part1(
-                r#".|...\....
+    static DATA: &str = r#".|...\....
 |.-.\.....
 .....|-...
 ........|.
@@ -225,32 +387,68 @@ mod tests {
 ..../.\\..
 .-.-/..|..
 .|....-|.\
-..//.|...."#
-                    .to_owned(),
-            )?,
-            46
-        );
+..//.|...."#;
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 46);
         Ok(())
     }
 
     #[test]
     fn test_part_2() -> Result<()> {
-        assert_eq!(
-            part2(
-                r#".|...\....
-|.-.\.....
-.....|-...
-........|.
-..........
-.........\
-..../.\\..
-.-.-/..|..
-.|....-|.\
-..//.|...."#
-                    .to_owned(),
-            )?,
-            51
-        );
+        assert_eq!(part2(DATA.to_owned())?, 51);
         Ok(())
     }
+
+    /// Plain tile by tile beam walk to check the graph against.
+    fn energized_by_bfs(grid: &Grid<Tile>, start: Beam) -> usize {
+        let mut energized = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut beams = vec![start];
+        while let Some(beam) = beams.pop() {
+            if !seen.insert(beam.clone()) {
+                continue;
+            }
+            energized.insert((beam.0, beam.1));
+            for dir in grid[(beam.0, beam.1)].direction_to(&beam.2) {
+                let next = beam.add_dir(&dir);
+                if grid.get(next.0, next.1).is_some() && (next.0, next.1) != (beam.0, beam.1) {
+                    beams.push(next);
+                }
+            }
+        }
+        energized.len()
+    }
+
+    #[test]
+    fn test_graph_matches_bfs_for_every_edge_start() {
+        let grid = Tile::new_tile_grid(DATA);
+        let graph = BeamGraph::new(&grid);
+        let starts = Beam::edge_starts(&grid);
+        assert_eq!(starts.len(), 40);
+        for start in starts {
+            assert_eq!(
+                graph.energized_from(&start).count(),
+                energized_by_bfs(&grid, start.clone()),
+                "{start:?}"
+            );
+        }
+        let lit = graph.energized_from(&Beam(0, 0, Dir::Right));
+        assert_eq!(graph.render(&lit).lines().next(), Some("######...."));
+    }
+
+    #[test]
+    fn test_sinks_first() {
+        // 0 -> 2 -> 1, 3 -> 0, 4 on its own.
+        let successors = vec![vec![2], vec![], vec![1], vec![0, 1], vec![]];
+        let order = sinks_first(&successors);
+        let position = |n: usize| order.iter().position(|o| *o == n);
+        assert_eq!(order.len(), 5);
+        for (node, next) in successors.iter().enumerate() {
+            for n in next {
+                assert!(position(*n) < position(node), "{order:?}");
+            }
+        }
+    }
 }
@@ -1,8 +1,5 @@
 use aoc23::prelude::*;
 use derive_builder::Builder;
-use geo::{Area, Coord, Coordinate, LineString, Polygon};
-use itertools::Itertools;
-use pathfinding::num_traits::{Float, Signed};
 use rayon::prelude::*;
 use std::{collections::HashSet, str::FromStr, time::Instant};
 
@@ -39,39 +36,54 @@ impl DigPlan {
         Ok(Self { commands })
     }
 
-    fn calculate_poly(&self) -> geo::Polygon {
+    fn dig_trench(&self) -> SparseGrid<()> {
         let mut position = (0, 0);
-        let mut visited: Vec<(i32, i32)> = vec![position];
+        let mut trench = SparseGrid::new();
         for command in &self.commands {
-            let (dx, dy) = match command.direction {
-                'U' => (0, -1),
-                'D' => (0, 1),
-                'L' => (-1, 0),
-                'R' => (1, 0),
+            let (dr, dc) = match command.direction {
+                'U' => (-1, 0),
+                'D' => (1, 0),
+                'L' => (0, -1),
+                'R' => (0, 1),
                 _ => unreachable!(),
             };
             for _ in 0..command.distance {
-                position.0 += dx;
-                position.1 += dy;
-                visited.push(position);
+                position.0 += dr;
+                position.1 += dc;
+                trench.insert(position, ());
             }
         }
-        if *visited.last().unwrap() != *visited.first().unwrap() {
+        if position != (0, 0) {
             panic!("expected a closed loop")
         }
-        if visited.iter().sorted_unstable().dedup().count() != visited.len() - 1 {
-            panic!("Loop intersected with itself more than the 1 allowed time for the start node");
+        trench
+    }
+
+    /// Flood fills the outside of the trench from one cell beyond its bounding box, anything not
+    /// reached is either trench or lagoon interior.
+    fn lagoon_size(trench: &SparseGrid<()>) -> usize {
+        let Some((min, max)) = trench.bounds() else {
+            return 0;
+        };
+        let (min, max) = ((min.0 - 1, min.1 - 1), (max.0 + 1, max.1 + 1));
+        let mut outside = HashSet::new();
+        let mut queue = vec![min];
+        while let Some(pos) = queue.pop() {
+            if pos.0 < min.0 || pos.1 < min.1 || pos.0 > max.0 || pos.1 > max.1 {
+                continue;
+            }
+            if trench.contains(&pos) || !outside.insert(pos) {
+                continue;
+            }
+            queue.extend([
+                (pos.0 - 1, pos.1),
+                (pos.0 + 1, pos.1),
+                (pos.0, pos.1 - 1),
+                (pos.0, pos.1 + 1),
+            ]);
         }
-        println!("visited {}: {visited:?}", visited.len());
-        let coordinates: Vec<Coordinate<f64>> = visited
-            .into_iter()
-            .map(|(x, y)| Coordinate {
-                x: x as f64,
-                y: y as f64,
-            })
-            .collect_vec();
-        Polygon::new(LineString::from(coordinates), vec![])
-        // visited.len()
+        let area = (max.0 - min.0 + 1) * (max.1 - min.1 + 1);
+        area as usize - outside.len()
     }
 }
 
@@ -85,15 +97,15 @@ fn part1(input: String) -> Result<usize> {
 
     // algo
     let a_start = Instant::now();
-    let polygon = dig_plan.calculate_poly();
-    let answer = polygon.signed_area().abs();
+    let trench = dig_plan.dig_trench();
+    let answer = DigPlan::lagoon_size(&trench);
     let algo_time = a_start.elapsed();
 
     // output
     println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
     println!("\tparse: {parsed_time:?}");
     println!("\talgo: {algo_time:?}");
-    Ok(answer as usize)
+    Ok(answer)
 }
 
 fn part2(input: String) -> Result<usize> {
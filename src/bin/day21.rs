@@ -0,0 +1,259 @@
+use aoc23::prelude::*;
+use grid::Grid;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
+use utils::math::sequence::{differences, value_at};
+
+static DAY: u8 = 21;
+
+#[derive(Debug, Clone)]
+struct Garden {
+    /// `true` for rocks.
+    rocks: Grid<bool>,
+    start: (usize, usize),
+}
+
+impl Garden {
+    fn new(s: &str) -> Result<Self> {
+        let mut rocks = Grid::init(0, 0, false);
+        let mut start = None;
+        for (row, line) in s.trim().lines().enumerate() {
+            let line = line.trim();
+            if rocks.rows() > 0 && line.len() != rocks.cols() {
+                return Err(format!("garden row {row} is not {} wide", rocks.cols()).into());
+            }
+            let mut cells = vec![];
+            for (col, char) in line.chars().enumerate() {
+                cells.push(match char {
+                    '#' => true,
+                    '.' => false,
+                    'S' if start.is_none() => {
+                        start = Some((row, col));
+                        false
+                    }
+                    'S' => return Err("more than one start".into()),
+                    unknown => return Err(format!("unknown garden tile {unknown:?}").into()),
+                });
+            }
+            rocks.push_row(cells);
+        }
+        Ok(Self {
+            rocks,
+            start: start.ok_or("no start in garden")?,
+        })
+    }
+
+    /// Steps from the start to every plot, on the map as given.
+    fn distances(&self) -> Grid<Option<usize>> {
+        let (rows, cols) = self.rocks.size();
+        let mut distances = Grid::init(rows, cols, None);
+        distances[self.start] = Some(0);
+        let mut queue = VecDeque::from([self.start]);
+        while let Some((row, col)) = queue.pop_front() {
+            let next_distance = distances[(row, col)].map(|d| d + 1);
+            for (r, c) in [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ] {
+                if self.rocks.get(r, c) == Some(&false) && distances[(r, c)].is_none() {
+                    distances[(r, c)] = next_distance;
+                    queue.push_back((r, c));
+                }
+            }
+        }
+        distances
+    }
+
+    /// Plots the elf can be on after exactly `steps`: reachable within that many and of the same
+    /// parity, since any plot can be left and come back to in two steps.
+    fn reachable(&self, steps: usize) -> usize {
+        self.distances()
+            .iter()
+            .flatten()
+            .filter(|d| **d <= steps && **d % 2 == steps % 2)
+            .count()
+    }
+
+    /// Same as [`Self::reachable`] but on the map tiled forever in every direction, for each of
+    /// `steps`, from one BFS out to the largest.
+    fn reachable_tiled(&self, steps: &[usize]) -> Vec<usize> {
+        let max_steps = steps.iter().copied().max().unwrap_or(0);
+        let (rows, cols) = (self.rocks.rows() as i64, self.rocks.cols() as i64);
+        let is_plot = |(row, col): (i64, i64)| {
+            !self.rocks[(row.rem_euclid(rows) as usize, col.rem_euclid(cols) as usize)]
+        };
+        let start = (self.start.0 as i64, self.start.1 as i64);
+        let mut distances: HashMap<(i64, i64), usize> = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some(((row, col), distance)) = queue.pop_front() {
+            if distance == max_steps {
+                continue;
+            }
+            for next in [
+                (row - 1, col),
+                (row + 1, col),
+                (row, col - 1),
+                (row, col + 1),
+            ] {
+                if is_plot(next) && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+        steps
+            .iter()
+            .map(|steps| {
+                distances
+                    .values()
+                    .filter(|d| **d <= *steps && **d % 2 == steps % 2)
+                    .count()
+            })
+            .collect()
+    }
+
+    /// Reachable plots on the tiled map for a huge step count.
+    ///
+    /// Once the frontier is several maps out, every extra map width of steps adds a ring of
+    /// whole maps whose size grows linearly, so the count at `rem + k * size` is quadratic in
+    /// `k`. How soon that settles depends on the map, so counts are sampled per period and the
+    /// quadratic is taken from the first spot where the third differences stay zero, then
+    /// evaluated exactly at the wanted `k`.
+    fn reachable_tiled_extrapolated(&self, steps: usize) -> Result<i128> {
+        let size = self.rocks.rows();
+        if size != self.rocks.cols() {
+            return Err(format!("garden is {:?}, expected a square", self.rocks.size()).into());
+        }
+        let (rem, k) = (steps % size, steps / size);
+        for periods in [5, 8, 12] {
+            if k < periods {
+                return Ok(self.reachable_tiled(&[steps])[0] as i128);
+            }
+            let samples = (0..periods).map(|k| rem + k * size).collect::<Vec<_>>();
+            let counts = self
+                .reachable_tiled(&samples)
+                .into_iter()
+                .map(|count| count as i64)
+                .collect::<Vec<_>>();
+            let third = differences(&counts).get(3).cloned().unwrap_or_default();
+            if let Some(settled) = third.windows(2).position(|w| w == [0, 0]) {
+                return Ok(value_at(
+                    &counts[settled..settled + 3],
+                    (k - settled) as i64,
+                ));
+            }
+        }
+        Err(format!("reachable counts for {steps} steps never settle into a quadratic").into())
+    }
+}
+
+fn part1(input: String, steps: usize) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let garden = Garden::new(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = garden.reachable(steps);
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+fn part2(input: String, steps: usize) -> Result<i128> {
+    // parse
+    let start = Instant::now();
+    let garden = Garden::new(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = garden.reachable_tiled_extrapolated(steps)?;
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Day {DAY}");
+    println!("=====");
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone(), 64)?;
+    part2(input.clone(), 26501365)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    /// The example with the start row and col cleared, like real inputs, so the ring growth
+    /// is regular.
+    static OPEN_DATA: &str = "...........
+.....#.#.#.
+.###...#.#.
+..#.#...#..
+....#.#....
+.....S.....
+.##......#.
+.......##..
+.##.#.####.
+.##..#..##.
+...........";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned(), 6)?, 16);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tiled_brute_force() -> Result<()> {
+        let garden = Garden::new(DATA)?;
+        assert_eq!(
+            garden.reachable_tiled(&[6, 10, 50, 100]),
+            vec![16, 50, 1594, 6536]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2_extrapolation_matches_brute_force() -> Result<()> {
+        let garden = Garden::new(OPEN_DATA)?;
+        for steps in [5 + 11 * 6, 5 + 11 * 9, 3 + 11 * 7, 10 + 11 * 8, 5 + 11 * 20] {
+            let brute = garden.reachable_tiled(&[steps])[0] as i128;
+            assert_eq!(
+                garden.reachable_tiled_extrapolated(steps)?,
+                brute,
+                "{steps}"
+            );
+        }
+        Ok(())
+    }
+}
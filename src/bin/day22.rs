@@ -0,0 +1,258 @@
+use aoc23::prelude::*;
+use grid::Grid;
+use itertools::Itertools;
+use std::{str::FromStr, time::Instant};
+
+static DAY: u8 = 22;
+
+/// Inclusive corners, `start` having the lower coordinate on every axis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Brick {
+    start: [usize; 3],
+    end: [usize; 3],
+}
+
+impl Brick {
+    fn footprint(&self) -> impl Iterator<Item = (usize, usize)> {
+        (self.start[0]..=self.end[0]).cartesian_product(self.start[1]..=self.end[1])
+    }
+
+    fn height(&self) -> usize {
+        self.end[2] - self.start[2] + 1
+    }
+}
+
+impl FromStr for Brick {
+    type Err = BoxE;
+
+    /// `1,0,1~1,2,1`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let corner = |c: &str| -> Result<[usize; 3]> {
+            let coords: Vec<usize> = c
+                .split(',')
+                .map(|n| n.trim().parse::<usize>())
+                .collect::<SResult<_, _>>()
+                .map_err(|e| format!("bad coordinate in {s:?}: {e}"))?;
+            coords
+                .try_into()
+                .map_err(|_| format!("expected 3 coordinates in {c:?}").into())
+        };
+        let (a, b) = s
+            .trim()
+            .split_once('~')
+            .ok_or_else(|| format!("no `~` in brick {s:?}"))?;
+        let (a, b) = (corner(a)?, corner(b)?);
+        Ok(Self {
+            start: [0, 1, 2].map(|i| a[i].min(b[i])),
+            end: [0, 1, 2].map(|i| a[i].max(b[i])),
+        })
+    }
+}
+
+/// Bricks after they have all fallen, with who rests on whom.
+#[derive(Debug)]
+struct Tower {
+    /// Settled bricks, in the order they landed, so every brick comes after its supporters.
+    bricks: Vec<Brick>,
+    supports: Vec<Vec<usize>>,
+    supported_by: Vec<Vec<usize>>,
+}
+
+impl Tower {
+    /// Drops bricks lowest first onto a height map of the top brick in every `(x, y)` column.
+    fn settle(mut bricks: Vec<Brick>) -> Self {
+        bricks.sort_by_key(|brick| brick.start[2]);
+        let cols = bricks.iter().map(|b| b.end[0] + 1).max().unwrap_or(0);
+        let rows = bricks.iter().map(|b| b.end[1] + 1).max().unwrap_or(0);
+        // Top z and the brick it belongs to, z 0 being the ground.
+        let mut heights: Grid<(usize, Option<usize>)> = Grid::init(cols, rows, (0, None));
+        let mut supports = vec![vec![]; bricks.len()];
+        let mut supported_by: Vec<Vec<usize>> = vec![vec![]; bricks.len()];
+        for (idx, brick) in bricks.iter_mut().enumerate() {
+            let rest_on = brick
+                .footprint()
+                .map(|pos| heights[pos].0)
+                .max()
+                .unwrap_or(0);
+            let height = brick.height();
+            brick.start[2] = rest_on + 1;
+            brick.end[2] = rest_on + height;
+            for pos in brick.footprint().collect_vec() {
+                if let (top, Some(below)) = heights[pos] {
+                    if top == rest_on && !supported_by[idx].contains(&below) {
+                        supported_by[idx].push(below);
+                        supports[below].push(idx);
+                    }
+                }
+                heights[pos] = (brick.end[2], Some(idx));
+            }
+        }
+        Self {
+            bricks,
+            supports,
+            supported_by,
+        }
+    }
+
+    /// Bricks whose removal drops nothing: everything they hold up has another supporter.
+    fn safe_to_disintegrate(&self) -> usize {
+        self.supports
+            .iter()
+            .filter(|above| above.iter().all(|a| self.supported_by[*a].len() > 1))
+            .count()
+    }
+
+    /// How many other bricks fall when each brick is removed.
+    ///
+    /// A brick falls exactly when every path of supports from the ground to it passes through
+    /// the removed brick, i.e. when the removed brick dominates it with the ground as root. The
+    /// landing order is topological, so each brick's immediate dominator is the common
+    /// dominator of all its supporters, and a brick brings down its dominator subtree.
+    fn chain_reactions(&self) -> Vec<usize> {
+        let n = self.bricks.len();
+        // `None` is the ground.
+        let mut idom: Vec<Option<usize>> = vec![None; n];
+        let mut depth = vec![0; n];
+        let depth_of = |depth: &[usize], node: Option<usize>| node.map_or(0, |n| depth[n] + 1);
+        for idx in 0..n {
+            let mut supporters = self.supported_by[idx].iter().map(|s| Some(*s));
+            let mut common = supporters.next().unwrap_or(None);
+            for other in supporters {
+                let mut other = other;
+                while common != other {
+                    if depth_of(&depth, common) >= depth_of(&depth, other) {
+                        common = common.and_then(|c| idom[c]);
+                    } else {
+                        other = other.and_then(|o| idom[o]);
+                    }
+                }
+            }
+            idom[idx] = common;
+            depth[idx] = depth_of(&depth, common);
+        }
+
+        let mut subtree = vec![1; n];
+        for idx in (0..n).rev() {
+            if let Some(parent) = idom[idx] {
+                subtree[parent] += subtree[idx];
+            }
+        }
+        subtree.into_iter().map(|size| size - 1).collect()
+    }
+}
+
+fn parse_bricks(input: &str) -> Result<Vec<Brick>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+fn part1(input: String) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let bricks = parse_bricks(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = Tower::settle(bricks).safe_to_disintegrate();
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+fn part2(input: String) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let bricks = parse_bricks(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = Tower::settle(bricks).chain_reactions().iter().sum();
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Day {DAY}");
+    println!("=====");
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    part2(input.clone())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA.to_owned())?, 7);
+        Ok(())
+    }
+
+    /// Removes each brick in turn and settles the rest again, counting bricks that moved.
+    fn chain_reactions_by_resettling(tower: &Tower) -> Vec<usize> {
+        (0..tower.bricks.len())
+            .map(|removed| {
+                let rest = tower
+                    .bricks
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| *idx != removed)
+                    .map(|(_, brick)| brick.clone())
+                    .collect_vec();
+                // Settling sorts stably by start z, which tells us where each brick ended up.
+                let landing = (0..rest.len()).sorted_by_key(|idx| rest[*idx].start[2]);
+                let resettled = Tower::settle(rest.clone());
+                landing
+                    .zip(resettled.bricks)
+                    .filter(|(idx, brick)| rest[*idx] != *brick)
+                    .count()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dominators_match_resettling() -> Result<()> {
+        let mut data = DATA.to_owned();
+        // A second stack leaning on two towers, so some bricks have shared supporters.
+        data.push_str("\n4,0,1~4,0,3\n6,0,1~6,0,3\n4,0,5~6,0,5\n5,0,7~5,0,7\n4,0,9~4,0,9");
+        let tower = Tower::settle(parse_bricks(&data)?);
+        assert_eq!(
+            tower.chain_reactions(),
+            chain_reactions_by_resettling(&tower)
+        );
+        // The example's 7, plus the beam across the second stack dropping the two on top of it.
+        assert_eq!(tower.chain_reactions().iter().sum::<usize>(), 9);
+        Ok(())
+    }
+}
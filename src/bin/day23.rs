@@ -0,0 +1,385 @@
+use aoc23::prelude::*;
+use grid::Grid;
+use rayon::prelude::*;
+use std::{collections::HashMap, time::Instant};
+
+static DAY: u8 = 23;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dir {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir {
+    const ALL: [Dir; 4] = [Dir::Up, Dir::Right, Dir::Down, Dir::Left];
+
+    fn step(&self, (row, col): (usize, usize)) -> (usize, usize) {
+        match self {
+            Dir::Up => (row.wrapping_sub(1), col),
+            Dir::Right => (row, col + 1),
+            Dir::Down => (row + 1, col),
+            Dir::Left => (row, col.wrapping_sub(1)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Tile {
+    #[default]
+    Forest,
+    Path,
+    Slope(Dir),
+}
+
+fn parse_trails(input: &str) -> Result<Grid<Tile>> {
+    let mut grid = Grid::new(0, 0);
+    for (row, line) in input.trim().lines().enumerate() {
+        let line = line.trim();
+        if grid.rows() > 0 && line.len() != grid.cols() {
+            return Err(format!("trail row {row} is not {} wide", grid.cols()).into());
+        }
+        let tiles = line
+            .chars()
+            .map(|c| match c {
+                '#' => Ok(Tile::Forest),
+                '.' => Ok(Tile::Path),
+                '^' => Ok(Tile::Slope(Dir::Up)),
+                '>' => Ok(Tile::Slope(Dir::Right)),
+                'v' => Ok(Tile::Slope(Dir::Down)),
+                '<' => Ok(Tile::Slope(Dir::Left)),
+                unknown => Err(format!("unknown trail tile {unknown:?}")),
+            })
+            .collect::<SResult<Vec<_>, _>>()?;
+        grid.push_row(tiles);
+    }
+    Ok(grid)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Corridor {
+    to: usize,
+    steps: usize,
+    /// Walkable this way without climbing a slope.
+    downhill: bool,
+}
+
+/// The trails compressed to junctions (plus start and end) joined by corridors, each at most
+/// 64 so a set of visited junctions fits in a `u64`.
+#[derive(Debug)]
+struct TrailMap {
+    corridors: Vec<Vec<Corridor>>,
+    start: usize,
+    end: usize,
+}
+
+impl TrailMap {
+    fn new(grid: &Grid<Tile>) -> Result<Self> {
+        if grid.rows() == 0 {
+            return Err("empty trail map".into());
+        }
+        let open = |pos: (usize, usize)| grid.get(pos.0, pos.1).is_some_and(|t| *t != Tile::Forest);
+        let first_open = |row: usize| {
+            (0..grid.cols())
+                .map(|col| (row, col))
+                .find(|pos| open(*pos))
+                .ok_or_else(|| format!("no path in row {row}"))
+        };
+        let start = first_open(0)?;
+        let end = first_open(grid.rows() - 1)?;
+        let mut junctions: HashMap<(usize, usize), usize> = HashMap::from([(start, 0), (end, 1)]);
+        for ((row, col), tile) in grid.indexed_iter() {
+            let exits = Dir::ALL.iter().filter(|d| open(d.step((row, col)))).count();
+            if *tile != Tile::Forest && exits > 2 {
+                let idx = junctions.len();
+                junctions.insert((row, col), idx);
+            }
+        }
+        if junctions.len() > 64 {
+            return Err(format!("{} junctions do not fit a u64 mask", junctions.len()).into());
+        }
+
+        let mut corridors = vec![vec![]; junctions.len()];
+        for (from, from_idx) in junctions.iter() {
+            for dir in Dir::ALL {
+                let (mut prev, mut pos, mut heading) = (*from, dir.step(*from), dir);
+                if !open(pos) {
+                    continue;
+                }
+                let mut steps = 1;
+                let mut downhill = true;
+                loop {
+                    if let Tile::Slope(slope) = grid[prev] {
+                        downhill &= slope == heading;
+                    }
+                    if let Some(to) = junctions.get(&pos) {
+                        corridors[*from_idx].push(Corridor {
+                            to: *to,
+                            steps,
+                            downhill,
+                        });
+                        break;
+                    }
+                    let Some(next_dir) = Dir::ALL
+                        .into_iter()
+                        .find(|d| d.step(pos) != prev && open(d.step(pos)))
+                    else {
+                        // Dead end.
+                        break;
+                    };
+                    (prev, pos, heading) = (pos, next_dir.step(pos), next_dir);
+                    steps += 1;
+                }
+            }
+        }
+        Ok(Self {
+            corridors,
+            start: 0,
+            end: 1,
+        })
+    }
+
+    /// Following slopes the corridors should form a DAG, so the longest hike is a memoized DFS.
+    /// Slopes that lead round in a circle are an error instead of endless recursion.
+    fn longest_downhill(&self) -> Result<Option<usize>> {
+        fn longest(
+            map: &TrailMap,
+            node: usize,
+            memo: &mut [Option<Option<usize>>],
+            on_path: &mut [bool],
+        ) -> Result<Option<usize>> {
+            if node == map.end {
+                return Ok(Some(0));
+            }
+            if let Some(known) = memo[node] {
+                return Ok(known);
+            }
+            if on_path[node] {
+                return Err("slopes lead round in a circle".into());
+            }
+            on_path[node] = true;
+            let mut best = None;
+            for c in map.corridors[node].iter().filter(|c| c.downhill) {
+                if let Some(rest) = longest(map, c.to, memo, on_path)? {
+                    best = best.max(Some(rest + c.steps));
+                }
+            }
+            on_path[node] = false;
+            memo[node] = Some(best);
+            Ok(best)
+        }
+        let nodes = self.corridors.len();
+        longest(
+            self,
+            self.start,
+            &mut vec![None; nodes],
+            &mut vec![false; nodes],
+        )
+    }
+
+    fn longest_from(&self, node: usize, visited: u64, target: usize) -> Option<usize> {
+        if node == target {
+            return Some(0);
+        }
+        self.corridors[node]
+            .iter()
+            .filter(|c| visited & (1 << c.to) == 0)
+            .filter_map(|c| {
+                self.longest_from(c.to, visited | 1 << c.to, target)
+                    .map(|rest| rest + c.steps)
+            })
+            .max()
+    }
+
+    /// Longest simple path ignoring slopes.
+    ///
+    /// The end has a single corridor in, and once its junction is reached the hike has to
+    /// finish there, so that junction is the target and its corridor is added at the end.
+    /// Paths are expanded a few junctions deep first and the rest is searched in parallel.
+    fn longest_any(&self) -> Option<usize> {
+        let (target, tail) = match self.corridors[self.end].as_slice() {
+            [only] => (only.to, only.steps),
+            _ => (self.end, 0),
+        };
+        let mut frontier = vec![(self.start, 1u64 << self.start, 0)];
+        for _ in 0..6 {
+            let mut next = vec![];
+            for (node, visited, steps) in frontier {
+                if node == target {
+                    next.push((node, visited, steps));
+                    continue;
+                }
+                for c in self.corridors[node].iter() {
+                    if visited & (1 << c.to) == 0 {
+                        next.push((c.to, visited | 1 << c.to, steps + c.steps));
+                    }
+                }
+            }
+            frontier = next;
+        }
+        frontier
+            .into_par_iter()
+            .filter_map(|(node, visited, steps)| {
+                self.longest_from(node, visited, target)
+                    .map(|rest| steps + rest + tail)
+            })
+            .max()
+    }
+}
+
+fn part1(input: String) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let map = TrailMap::new(&parse_trails(&input)?)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = map.longest_downhill()?.ok_or("no downhill hike")?;
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+fn part2(input: String) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let map = TrailMap::new(&parse_trails(&input)?)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = map.longest_any().ok_or("no hike")?;
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Day {DAY}");
+    println!("=====");
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    part2(input.clone())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &str = "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 94);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA.to_owned())?, 154);
+        Ok(())
+    }
+
+    /// Tile by tile backtracking over the whole grid.
+    fn longest_by_tiles(
+        grid: &Grid<Tile>,
+        pos: (usize, usize),
+        seen: &mut Grid<bool>,
+        slopes: bool,
+    ) -> Option<usize> {
+        if pos.0 + 1 == grid.rows() {
+            return Some(0);
+        }
+        let mut best = None;
+        for dir in Dir::ALL {
+            if let (true, Tile::Slope(slope)) = (slopes, grid[pos]) {
+                if slope != dir {
+                    continue;
+                }
+            }
+            let next = dir.step(pos);
+            if grid.get(next.0, next.1).is_some_and(|t| *t != Tile::Forest) && !seen[next] {
+                seen[next] = true;
+                let rest = longest_by_tiles(grid, next, seen, slopes).map(|r| r + 1);
+                best = best.max(rest);
+                seen[next] = false;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_compressed_matches_tile_walk() -> Result<()> {
+        let grid = parse_trails(DATA)?;
+        let map = TrailMap::new(&grid)?;
+        let (rows, cols) = grid.size();
+        for (slopes, compressed) in [(true, map.longest_downhill()?), (false, map.longest_any())] {
+            let mut seen = Grid::init(rows, cols, false);
+            seen[(0, 1)] = true;
+            assert_eq!(
+                longest_by_tiles(&grid, (0, 1), &mut seen, slopes),
+                compressed
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_maps_are_errors() {
+        assert!(part1("".to_owned()).is_err());
+        assert!(part2("\n\n".to_owned()).is_err());
+
+        // start -> 2 -> 3 -> 2 with every corridor downhill.
+        let corridor = |to| Corridor {
+            to,
+            steps: 1,
+            downhill: true,
+        };
+        let map = TrailMap {
+            corridors: vec![
+                vec![corridor(2)],
+                vec![],
+                vec![corridor(3)],
+                vec![corridor(2), corridor(1)],
+            ],
+            start: 0,
+            end: 1,
+        };
+        assert!(map.longest_downhill().is_err());
+    }
+}
@@ -0,0 +1,305 @@
+use aoc23::prelude::*;
+use itertools::Itertools;
+use std::{str::FromStr, time::Instant};
+use utils::math::{mod_inverse, rational::Rational};
+
+static DAY: u8 = 24;
+
+type Vec3 = [i128; 3];
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hailstone {
+    pos: Vec3,
+    vel: Vec3,
+}
+
+impl FromStr for Hailstone {
+    type Err = BoxE;
+
+    /// `19, 13, 30 @ -2,  1, -2`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let triple = |part: &str| -> Result<Vec3> {
+            let values: Vec<i128> = part
+                .split(',')
+                .map(|n| n.trim().parse::<i128>())
+                .collect::<SResult<_, _>>()
+                .map_err(|e| format!("bad number in {s:?}: {e}"))?;
+            values
+                .try_into()
+                .map_err(|_| format!("expected 3 values in {part:?}").into())
+        };
+        let (pos, vel) = s
+            .split_once('@')
+            .ok_or_else(|| format!("no `@` in hailstone {s:?}"))?;
+        Ok(Self {
+            pos: triple(pos)?,
+            vel: triple(vel)?,
+        })
+    }
+}
+
+impl Hailstone {
+    fn parse_all(input: &str) -> Result<Vec<Self>> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Where the x/y paths of two hailstones cross, if both get there in the future. Parallel
+    /// paths never cross.
+    fn crossing_xy(&self, other: &Self) -> Option<(Rational, Rational)> {
+        let det = self.vel[0] * other.vel[1] - self.vel[1] * other.vel[0];
+        if det == 0 {
+            return None;
+        }
+        let dx = other.pos[0] - self.pos[0];
+        let dy = other.pos[1] - self.pos[1];
+        let t = Rational::new(dx * other.vel[1] - dy * other.vel[0], det);
+        let s = Rational::new(dx * self.vel[1] - dy * self.vel[0], det);
+        if t < Rational::ZERO || s < Rational::ZERO {
+            return None;
+        }
+        Some((
+            Rational::from(self.pos[0]) + t * Rational::from(self.vel[0]),
+            Rational::from(self.pos[1]) + t * Rational::from(self.vel[1]),
+        ))
+    }
+}
+
+fn crossings_in_area(hailstones: &[Hailstone], min: i128, max: i128) -> usize {
+    let area = Rational::from(min)..=Rational::from(max);
+    hailstones
+        .iter()
+        .tuple_combinations()
+        .filter_map(|(a, b)| a.crossing_xy(b))
+        .filter(|(x, y)| area.contains(x) && area.contains(y))
+        .count()
+}
+
+/// Solves `a * x = b` modulo a prime by Gauss-Jordan elimination. `None` if the system is
+/// singular mod `prime`.
+fn solve_mod(mut rows: Vec<Vec<i128>>, prime: i128) -> Option<Vec<i128>> {
+    let n = rows.len();
+    for row in rows.iter_mut() {
+        row.iter_mut().for_each(|v| *v = v.rem_euclid(prime));
+    }
+    for col in 0..n {
+        let pivot = (col..n).find(|r| rows[*r][col] != 0)?;
+        rows.swap(col, pivot);
+        let inverse = mod_inverse(rows[col][col], prime)?;
+        rows[col].iter_mut().for_each(|v| *v = *v * inverse % prime);
+        let pivot_row = rows[col].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if r != col && factor != 0 {
+                for (v, p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *v = (*v - factor * p).rem_euclid(prime);
+                }
+            }
+        }
+    }
+    Some(rows.iter().map(|row| row[n]).collect())
+}
+
+/// When a rock thrown from `pos` at `vel` hits `hailstone`, if it does at a whole,
+/// non-negative time. `pos + vel * t = h.pos + h.vel * t` per axis, and an axis where the two
+/// move together has to start level.
+fn hit_time(pos: Vec3, vel: Vec3, hailstone: &Hailstone) -> Option<i128> {
+    let mut time = None;
+    for (gap, closing) in sub(hailstone.pos, pos)
+        .into_iter()
+        .zip(sub(vel, hailstone.vel))
+    {
+        if closing == 0 {
+            if gap != 0 {
+                return None;
+            }
+            continue;
+        }
+        if gap % closing != 0 {
+            return None;
+        }
+        let t = gap / closing;
+        if t < 0 || time.is_some_and(|time| time != t) {
+            return None;
+        }
+        time = Some(t);
+    }
+    Some(time.unwrap_or(0))
+}
+
+/// Position and velocity of a rock that hits every hailstone.
+///
+/// Hitting hailstone `i` means `(P - p_i) × (V - v_i) = 0`. The `P × V` term is the same for
+/// every hailstone, so subtracting two of these leaves three equations linear in `P` and `V`:
+/// `P × (v_j - v_i) + (p_j - p_i) × V = p_j × v_j - p_i × v_i`. Two pairs give six equations.
+///
+/// Exact elimination over the integers grows intermediates to the size of 6x6 minors, which
+/// overflows `i128` for real inputs, so the system is solved modulo a prime well beyond any
+/// coordinate and the residues are read back as signed values in `±2^60`. A rock outside that
+/// range comes back wrapped, so every candidate has to hit every hailstone at a whole,
+/// non-negative time before it is returned; anything else, including an unlucky prime, gives
+/// `None` rather than a wrong throw.
+fn throw_rock(hailstones: &[Hailstone]) -> Option<(Vec3, Vec3)> {
+    const PRIME: i128 = (1 << 61) - 1;
+    let equations = |i: &Hailstone, j: &Hailstone| {
+        let w = sub(j.vel, i.vel);
+        let u = sub(j.pos, i.pos);
+        let rhs = sub(cross(j.pos, j.vel), cross(i.pos, i.vel));
+        [
+            vec![0, w[2], -w[1], 0, -u[2], u[1], rhs[0]],
+            vec![-w[2], 0, w[0], u[2], 0, -u[0], rhs[1]],
+            vec![w[1], -w[0], 0, -u[1], u[0], 0, rhs[2]],
+        ]
+    };
+    let hits_all =
+        |pos: Vec3, vel: Vec3| hailstones.iter().all(|h| hit_time(pos, vel, h).is_some());
+    let first = hailstones.first()?;
+    hailstones[1..]
+        .iter()
+        .tuple_combinations()
+        .find_map(|(a, b)| {
+            let rows = equations(first, a)
+                .into_iter()
+                .chain(equations(first, b))
+                .collect();
+            let solution = solve_mod(rows, PRIME)?
+                .into_iter()
+                .map(|v| if v > PRIME / 2 { v - PRIME } else { v })
+                .collect_vec();
+            let (pos, vel) = (
+                [solution[0], solution[1], solution[2]],
+                [solution[3], solution[4], solution[5]],
+            );
+            hits_all(pos, vel).then_some((pos, vel))
+        })
+}
+
+fn part1(input: String, min: i128, max: i128) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let hailstones = Hailstone::parse_all(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let answer = crossings_in_area(&hailstones, min, max);
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+fn part2(input: String) -> Result<i128> {
+    // parse
+    let start = Instant::now();
+    let hailstones = Hailstone::parse_all(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let (pos, _) = throw_rock(&hailstones).ok_or("no rock throw hits every hailstone")?;
+    let answer = pos.iter().sum();
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 2: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    Ok(answer)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Day {DAY}");
+    println!("=====");
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone(), 200000000000000, 400000000000000)?;
+    part2(input.clone())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &str = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned(), 7, 27)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part2(DATA.to_owned())?, 47);
+        Ok(())
+    }
+
+    #[test]
+    fn test_crossings_are_exact() -> Result<()> {
+        let hailstones = Hailstone::parse_all(DATA)?;
+        let (x, y) = hailstones[0]
+            .crossing_xy(&hailstones[1])
+            .ok_or("no crossing")?;
+        assert_eq!((x, y), (Rational::new(43, 3), Rational::new(46, 3)));
+        // Parallel, and crossed in the past.
+        assert_eq!(hailstones[1].crossing_xy(&hailstones[2]), None);
+        assert_eq!(hailstones[0].crossing_xy(&hailstones[4]), None);
+        assert_eq!(throw_rock(&hailstones), Some(([24, 13, 10], [-3, 1, 2])));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rock_must_hit_in_the_future() -> Result<()> {
+        let hailstones = Hailstone::parse_all(DATA)?;
+        let (pos, vel) = ([24, 13, 10], [-3, 1, 2]);
+        let times = hailstones
+            .iter()
+            .map(|h| hit_time(pos, vel, h))
+            .collect_vec();
+        assert_eq!(times, vec![Some(5), Some(3), Some(4), Some(6), Some(1)]);
+
+        // The same lines, but every meeting happened before the throw.
+        let past = hailstones
+            .iter()
+            .zip([5, 3, 4, 6, 1])
+            .map(|(h, t)| Hailstone {
+                pos: [0, 1, 2].map(|i| pos[i] - t * (vel[i] - h.vel[i])),
+                vel: h.vel,
+            })
+            .collect_vec();
+        assert_eq!(hit_time(pos, vel, &past[0]), None);
+        assert_eq!(throw_rock(&past), None);
+
+        // Meeting halfway between two ticks is not a hit either.
+        let halfway = Hailstone {
+            pos: [25, 13, 10],
+            vel: [-5, 1, 2],
+        };
+        assert_eq!(hit_time(pos, vel, &halfway), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use aoc23::prelude::*;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+    time::Instant,
+};
+
+static DAY: u8 = 25;
+
+/// Component names mapped to dense indices and back.
+#[derive(Debug, Default)]
+struct Interner {
+    ids: HashMap<String, usize>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), self.names.len() - 1);
+        self.names.len() - 1
+    }
+
+    fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cut {
+    /// Wires to disconnect, as edge indices.
+    edges: Vec<usize>,
+    /// Which side of the cut each component ends up on, `true` being the side with the first
+    /// component.
+    side: Vec<bool>,
+}
+
+impl Cut {
+    fn sizes(&self) -> (usize, usize) {
+        let first = self.side.iter().filter(|s| **s).count();
+        (first, self.side.len() - first)
+    }
+}
+
+#[derive(Debug)]
+struct Wiring {
+    names: Interner,
+    edges: Vec<(usize, usize)>,
+    /// Neighbour and edge index for every component.
+    adjacent: Vec<Vec<(usize, usize)>>,
+}
+
+impl Wiring {
+    /// `jqt: rhn xhk nvd`, one line per component listing some of its wires.
+    fn new(input: &str) -> Result<Self> {
+        let mut names = Interner::default();
+        let mut edges = vec![];
+        for (idx, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (from, to) = line
+                .split_once(':')
+                .ok_or_else(|| format!("line {}: no `:` in {line:?}", idx + 1))?;
+            let from = names.intern(from.trim());
+            for to in to.split_whitespace() {
+                edges.push((from, names.intern(to)));
+            }
+        }
+        let mut adjacent = vec![vec![]; names.names.len()];
+        for (idx, (a, b)) in edges.iter().enumerate() {
+            adjacent[*a].push((*b, idx));
+            adjacent[*b].push((*a, idx));
+        }
+        Ok(Self {
+            names,
+            edges,
+            adjacent,
+        })
+    }
+
+    /// Max flow from `source` to `sink` with every wire carrying 1 either way, giving up once it
+    /// reaches `limit`. Returns the flow and the components still reachable from `source` in
+    /// the residual graph, which is the source side of a minimum `source`/`sink` cut.
+    fn max_flow(&self, source: usize, sink: usize, limit: usize) -> (usize, Vec<bool>) {
+        // Flow along each edge in its `(a, b)` direction, negative for `b` to `a`.
+        let mut flow = vec![0i8; self.edges.len()];
+        let residual = |flow: &[i8], from: usize, edge: usize| {
+            if self.edges[edge].0 == from {
+                flow[edge] < 1
+            } else {
+                flow[edge] > -1
+            }
+        };
+        let mut total = 0;
+        loop {
+            let mut came_from: Vec<Option<(usize, usize)>> = vec![None; self.adjacent.len()];
+            let mut reached = vec![false; self.adjacent.len()];
+            reached[source] = true;
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                for (next, edge) in self.adjacent[node].iter() {
+                    if !reached[*next] && residual(&flow, node, *edge) {
+                        reached[*next] = true;
+                        came_from[*next] = Some((node, *edge));
+                        queue.push_back(*next);
+                    }
+                }
+            }
+            if !reached[sink] || total == limit {
+                return (total, reached);
+            }
+            let mut node = sink;
+            while let Some((prev, edge)) = came_from[node] {
+                flow[edge] += if self.edges[edge].0 == prev { 1 } else { -1 };
+                node = prev;
+            }
+            total += 1;
+        }
+    }
+
+    /// Global minimum cut: every cut separates the first component from some other one, so it
+    /// is the smallest of the max flows from the first component to each of the others.
+    fn min_cut(&self) -> Option<Cut> {
+        let mut best: Option<(usize, Vec<bool>)> = None;
+        for sink in 1..self.adjacent.len() {
+            let limit = best.as_ref().map_or(usize::MAX, |(flow, _)| *flow);
+            let (flow, side) = self.max_flow(0, sink, limit);
+            if flow < limit {
+                best = Some((flow, side));
+            }
+        }
+        best.map(|(_, side)| Cut {
+            edges: (0..self.edges.len())
+                .filter(|e| side[self.edges[*e].0] != side[self.edges[*e].1])
+                .collect(),
+            side,
+        })
+    }
+
+    fn edge_names(&self, edge: usize) -> (&str, &str) {
+        let (a, b) = self.edges[edge];
+        (self.names.name(a), self.names.name(b))
+    }
+
+    /// The wiring as a Graphviz graph, cut wires in red and the two sides in different colors.
+    /// Written to `AOC_DOT_FILE` when it is set.
+    fn to_dot(&self, cut: &Cut) -> String {
+        let mut out = String::from("graph wiring {\n");
+        for (id, name) in self.names.names.iter().enumerate() {
+            let color = if cut.side[id] {
+                "lightblue"
+            } else {
+                "lightpink"
+            };
+            let _ = writeln!(out, "  \"{name}\" [style=filled, fillcolor={color}];");
+        }
+        for edge in 0..self.edges.len() {
+            let (a, b) = self.edge_names(edge);
+            let style = if cut.edges.contains(&edge) {
+                " [color=red, penwidth=3]"
+            } else {
+                ""
+            };
+            let _ = writeln!(out, "  \"{a}\" -- \"{b}\"{style};");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn part1(input: String) -> Result<usize> {
+    // parse
+    let start = Instant::now();
+    let wiring = Wiring::new(&input)?;
+    let parsed_time = start.elapsed();
+
+    // algo
+    let a_start = Instant::now();
+    let cut = wiring.min_cut().ok_or("fewer than two components")?;
+    if cut.edges.len() != 3 {
+        return Err(format!("minimum cut is {} wires, not 3", cut.edges.len()).into());
+    }
+    for edge in cut.edges.iter() {
+        let (a, b) = wiring.edge_names(*edge);
+        println!("\tcut {a}/{b}");
+    }
+    let (a, b) = cut.sizes();
+    let answer = a * b;
+    let algo_time = a_start.elapsed();
+
+    // output
+    println!("part 1: {answer}\t[total: {:?}]", start.elapsed());
+    println!("\tparse: {parsed_time:?}");
+    println!("\talgo: {algo_time:?}");
+    if let Some(path) = &get_config().dot_file {
+        std::fs::write(path, wiring.to_dot(&cut))?;
+        println!("\twrote {path}");
+    }
+    Ok(answer)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Day {DAY}");
+    println!("=====");
+    let input = utils::aoc::get_puzzle_input(DAY).await?;
+    part1(input.clone())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    static DATA: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    #[test]
+    fn test_part_1() -> Result<()> {
+        assert_eq!(part1(DATA.to_owned())?, 54);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cut_edges_and_dot() -> Result<()> {
+        let wiring = Wiring::new(DATA)?;
+        let cut = wiring.min_cut().ok_or("no cut")?;
+        let cut_names = cut
+            .edges
+            .iter()
+            .map(|e| {
+                let (a, b) = wiring.edge_names(*e);
+                if a < b {
+                    (a, b)
+                } else {
+                    (b, a)
+                }
+            })
+            .sorted()
+            .collect_vec();
+        assert_eq!(
+            cut_names,
+            vec![("bvb", "cmg"), ("hfx", "pzl"), ("jqt", "nvd")]
+        );
+        assert_eq!(cut.sizes(), (6, 9));
+        let dot = wiring.to_dot(&cut);
+        assert!(dot.contains("\"pzl\" -- \"hfx\" [color=red, penwidth=3];"));
+        assert_eq!(dot.matches("color=red").count(), 3);
+        Ok(())
+    }
+}
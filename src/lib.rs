@@ -8,5 +8,6 @@ pub mod prelude {
         common::{BoxE, Result, SError, SResult},
         config::get_config,
         grid::GridExtras,
+        sparse_grid::SparseGrid,
     };
 }
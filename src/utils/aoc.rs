@@ -1,43 +1,428 @@
 use crate::prelude::*;
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
 use reqwest::{
     header::{self, COOKIE},
-    Client,
+    Client, StatusCode,
 };
 use reqwest_middleware::{ClientBuilder as MiddlewareClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::OnceCell;
 use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 static AOC_PUZZLE_INPUT_CACHE: &str = "aoc_puzzle_cache";
+static AOC_YEAR: u16 = 2023;
+/// Start of every encrypted cache file, followed by the nonce and the ciphertext.
+static ENCRYPTED_MAGIC: &[u8] = b"aoc-encrypted-v1\n";
+
+/// Bodies AoC serves with (or instead of) an input that must never be cached as one.
+static KNOWN_ERROR_BODIES: [(&str, &str); 4] = [
+    ("Please log in", "not logged in, check AOC_SESSION"),
+    ("before it unlocks", "puzzle has not unlocked yet"),
+    ("404 Not Found", "page not found"),
+    ("500 Internal Server Error", "server error"),
+];
 
 pub async fn get_puzzle_input(day: u8) -> Result<String> {
-    let file_name = format!("day_{:02}", day);
-    let cache_path = PathBuf::from(AOC_PUZZLE_INPUT_CACHE).join(file_name);
+    let cache_path = cache_path(Path::new(AOC_PUZZLE_INPUT_CACHE), day);
     if cache_path.exists() {
         // Read from the cache file
-        let mut cache_file = File::open(cache_path)?;
-        let mut contents = String::new();
-        cache_file.read_to_string(&mut contents)?;
-        Ok(contents)
+        let contents = read_cached(&cache_path, &KeySource::local())?;
+        let metadata = CacheMetadata::load(&cache_path)?;
+        let session = session_fingerprint(&get_config().aoc_session);
+        let problem = diagnose(&contents).or_else(|| {
+            let metadata = metadata.as_ref()?;
+            metadata.check(day, &contents).or_else(|| {
+                (!get_config().aoc_session.is_empty() && metadata.session_fingerprint != session)
+                    .then(|| "fetched with a different session".to_owned())
+            })
+        });
+        match problem {
+            None => return Ok(contents),
+            Some(problem) => eprintln!(
+                "ignoring cached {}: {problem}, fetching again",
+                cache_path.display()
+            ),
+        }
+    }
+
+    // Fetch the content from the URL
+    let response = aoc_client()
+        .await?
+        .get(format!(
+            "https://adventofcode.com/{AOC_YEAR}/day/{day}/input"
+        ))
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.text().await?;
+    check_response(day, status, &body)?;
+
+    // Cache the content to a file Ensure the cache directory exists
+    fs::create_dir_all(AOC_PUZZLE_INPUT_CACHE)?;
+    let key = if get_config().cache_encryption {
+        Some(CacheKey::load_or_create(&CacheKey::path()?)?)
     } else {
-        // Fetch the content from the URL
-        let response = aoc_client()
-            .await?
-            .get(format!("https://adventofcode.com/2023/day/{}/input", day))
-            .send()
-            .await?
-            .text()
-            .await?;
+        None
+    };
+    write_cached(&cache_path, &body, key.as_ref())?;
+    CacheMetadata::new(day, &body, &get_config().aoc_session).save(&cache_path)?;
+    Ok(body)
+}
+
+fn cache_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day_{:02}", day))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Short stable id for a session cookie, enough to tell accounts apart without storing the
+/// cookie itself.
+pub fn session_fingerprint(session: &str) -> String {
+    sha256_hex(format!("aoc-session:{session}").as_bytes())[..16].to_owned()
+}
+
+/// Sidecar stored next to each cached input as `day_NN.meta.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheMetadata {
+    pub year: u16,
+    pub day: u8,
+    /// Seconds since the unix epoch.
+    pub fetched_at: u64,
+    pub session_fingerprint: String,
+    pub sha256: String,
+}
+
+impl CacheMetadata {
+    fn new(day: u8, contents: &str, session: &str) -> Self {
+        Self {
+            year: AOC_YEAR,
+            day,
+            fetched_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            session_fingerprint: session_fingerprint(session),
+            sha256: sha256_hex(contents.as_bytes()),
+        }
+    }
+
+    fn sidecar_path(cache_path: &Path) -> PathBuf {
+        cache_path.with_extension("meta.json")
+    }
+
+    /// The sidecar for a cached input, `None` for inputs cached before sidecars existed.
+    pub fn load(cache_path: &Path) -> Result<Option<Self>> {
+        let path = Self::sidecar_path(cache_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let metadata = serde_json::from_slice(&fs::read(&path)?)
+            .map_err(|e| format!("unreadable metadata {}: {e}", path.display()))?;
+        Ok(Some(metadata))
+    }
+
+    fn save(&self, cache_path: &Path) -> Result<()> {
+        write_atomically(
+            &Self::sidecar_path(cache_path),
+            serde_json::to_string_pretty(self)?.as_bytes(),
+        )
+    }
+
+    /// What disagrees between this metadata and the input it describes, if anything.
+    fn check(&self, day: u8, contents: &str) -> Option<String> {
+        if (self.year, self.day) != (AOC_YEAR, day) {
+            return Some(format!(
+                "metadata is for {} day {}, not {AOC_YEAR} day {day}",
+                self.year, self.day
+            ));
+        }
+        (sha256_hex(contents.as_bytes()) != self.sha256)
+            .then(|| "content does not match its recorded sha256".to_owned())
+    }
+}
+
+/// Errors for anything but a successful response with a plausible input in it.
+fn check_response(day: u8, status: StatusCode, body: &str) -> Result<()> {
+    let problem = diagnose(body);
+    if !status.is_success() {
+        let reason = problem.unwrap_or_else(|| body.lines().next().unwrap_or("").to_owned());
+        return Err(format!("fetching day {day} input failed with {status}: {reason}").into());
+    }
+    match problem {
+        Some(problem) => Err(format!("day {day} input looks wrong: {problem}").into()),
+        None => Ok(()),
+    }
+}
+
+/// Why `contents` does not look like a puzzle input, if it doesn't.
+pub fn diagnose(contents: &str) -> Option<String> {
+    if contents.trim().is_empty() {
+        return Some("empty".to_owned());
+    }
+    if let Some((_, reason)) = KNOWN_ERROR_BODIES
+        .iter()
+        .find(|(needle, _)| contents.contains(needle))
+    {
+        return Some(reason.to_string());
+    }
+    let start = contents.trim_start().to_ascii_lowercase();
+    if start.starts_with("<!doctype") || start.starts_with("<html") {
+        return Some("an html page".to_owned());
+    }
+    if contents
+        .chars()
+        .any(|c| !(c.is_ascii_graphic() || c.is_ascii_whitespace()))
+    {
+        return Some("contains non printable or non ascii characters".to_owned());
+    }
+    None
+}
 
-        // Cache the content to a file Ensure the cache directory exists
-        fs::create_dir_all(AOC_PUZZLE_INPUT_CACHE)?;
-        let mut cache_file = File::create(cache_path)?;
-        cache_file.write_all(response.as_bytes())?;
-        Ok(response)
+/// Writes next to `path` first and renames over it, so an interrupted write never leaves a
+/// partial file where the input should be.
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut tmp_file = File::create(&tmp_path)?;
+    tmp_file.write_all(contents)?;
+    tmp_file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Key for the encrypted cache, kept as hex in a local file outside the repo.
+pub struct CacheKey(Key);
+
+impl CacheKey {
+    pub fn path() -> Result<PathBuf> {
+        Ok(expanduser::expanduser(&get_config().aoc_key_file)?)
+    }
+
+    /// `None` when there is no key file yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let hex = fs::read_to_string(path)?;
+        let hex = hex.trim();
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|b| u8::from_str_radix(b, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .filter(|bytes| bytes.len() == 32)
+            .ok_or_else(|| format!("{} is not a 64 digit hex key", path.display()))?;
+        Ok(Some(Self(*Key::from_slice(&bytes))))
+    }
+
+    /// Loads the key, generating and saving a new one (readable only by the owner) if there is
+    /// none.
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if let Some(key) = Self::load(path)? {
+            return Ok(key);
+        }
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let hex = key.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(path)?
+            .write_all(format!("{hex}\n").as_bytes())?;
+        Ok(Self(key))
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&self.0)
+            .encrypt(&nonce, plaintext)
+            .map_err(|e| format!("encrypting cache entry failed: {e}"))?;
+        Ok([ENCRYPTED_MAGIC, nonce.as_slice(), &ciphertext].concat())
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let data = data
+            .strip_prefix(ENCRYPTED_MAGIC)
+            .filter(|rest| rest.len() >= 12)
+            .ok_or("not an encrypted cache entry")?;
+        let (nonce, ciphertext) = data.split_at(12);
+        Ok(ChaCha20Poly1305::new(&self.0)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "cache entry does not decrypt with this key")?)
     }
 }
 
+/// Where the key for encrypted cache entries comes from. The local key file is only read the
+/// first time an encrypted entry needs it, so plaintext caches never touch it.
+enum KeySource<'a> {
+    Local(OnceCell<Option<CacheKey>>),
+    Given(Option<&'a CacheKey>),
+}
+
+impl KeySource<'_> {
+    fn local() -> Self {
+        Self::Local(OnceCell::new())
+    }
+
+    fn get(&self) -> Result<Option<&CacheKey>> {
+        match self {
+            Self::Local(cell) => {
+                if let Some(key) = cell.get() {
+                    return Ok(key.as_ref());
+                }
+                let key = CacheKey::load(&CacheKey::path()?)?;
+                Ok(cell.get_or_init(|| key).as_ref())
+            }
+            Self::Given(key) => Ok(*key),
+        }
+    }
+}
+
+fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTED_MAGIC)
+}
+
+/// Reads a cached input, plaintext or encrypted.
+fn read_cached(path: &Path, key: &KeySource) -> Result<String> {
+    let data = fs::read(path)?;
+    if !is_encrypted(&data) {
+        return Ok(String::from_utf8(data)?);
+    }
+    let key = key
+        .get()?
+        .ok_or_else(|| format!("{} is encrypted but there is no key", path.display()))?;
+    Ok(String::from_utf8(key.decrypt(&data)?)?)
+}
+
+/// Writes a cached input, encrypted when there is a key.
+fn write_cached(path: &Path, contents: &str, key: Option<&CacheKey>) -> Result<()> {
+    match key {
+        Some(key) => write_atomically(path, &key.encrypt(contents.as_bytes())?),
+        None => write_atomically(path, contents.as_bytes()),
+    }
+}
+
+/// Encrypts every plaintext input in the cache in place, creating the key if needed, and
+/// returns the files it encrypted. Sidecars describe the plaintext, so they stay valid.
+pub fn encrypt_cache() -> Result<Vec<PathBuf>> {
+    let key = CacheKey::load_or_create(&CacheKey::path()?)?;
+    encrypt_cache_in(Path::new(AOC_PUZZLE_INPUT_CACHE), &key)
+}
+
+pub fn encrypt_cache_in(dir: &Path, key: &CacheKey) -> Result<Vec<PathBuf>> {
+    let mut encrypted = vec![];
+    if !dir.exists() {
+        return Ok(encrypted);
+    }
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<SResult<Vec<_>, _>>()?;
+    paths.sort();
+    for path in paths {
+        if cache_file_day(&path).is_none() || is_encrypted(&fs::read(&path)?) {
+            continue;
+        }
+        let contents = read_cached(&path, &KeySource::Given(None))?;
+        write_cached(&path, &contents, Some(key))?;
+        encrypted.push(path);
+    }
+    Ok(encrypted)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheReport {
+    pub path: PathBuf,
+    /// What looks wrong with the file, `None` if it looks like an input.
+    pub problem: Option<String>,
+    pub metadata: Option<CacheMetadata>,
+}
+
+/// Checks every file in the puzzle input cache.
+pub fn cache_doctor() -> Result<Vec<CacheReport>> {
+    cache_doctor_in(Path::new(AOC_PUZZLE_INPUT_CACHE))
+}
+
+pub fn cache_doctor_in(dir: &Path) -> Result<Vec<CacheReport>> {
+    cache_doctor_with(dir, &KeySource::local())
+}
+
+pub fn cache_doctor_with_key(dir: &Path, key: Option<&CacheKey>) -> Result<Vec<CacheReport>> {
+    cache_doctor_with(dir, &KeySource::Given(key))
+}
+
+fn cache_doctor_with(dir: &Path, key: &KeySource) -> Result<Vec<CacheReport>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<SResult<Vec<_>, _>>()?;
+    paths.sort();
+    let mut reports = vec![];
+    for path in paths {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if let Some(input) = name.strip_suffix(".meta.json") {
+            // Sidecars are checked with their input, only orphans are worth a report.
+            if !dir.join(input).exists() {
+                reports.push(CacheReport {
+                    problem: Some("metadata without an input".to_owned()),
+                    path,
+                    metadata: None,
+                });
+            }
+            continue;
+        }
+        let (problem, metadata) = match cache_file_day(&path) {
+            None if path.extension().is_some_and(|e| e == "tmp") => {
+                (Some("left over from an interrupted write".to_owned()), None)
+            }
+            None => (Some("not named day_01 to day_25".to_owned()), None),
+            Some(day) => match (read_cached(&path, key), CacheMetadata::load(&path)) {
+                (Ok(contents), Ok(metadata)) => (
+                    diagnose(&contents).or_else(|| metadata.as_ref()?.check(day, &contents)),
+                    metadata,
+                ),
+                (Err(e), metadata) => (Some(e.to_string()), metadata.ok().flatten()),
+                (Ok(_), Err(e)) => (Some(e.to_string()), None),
+            },
+        };
+        reports.push(CacheReport {
+            path,
+            problem,
+            metadata,
+        });
+    }
+    Ok(reports)
+}
+
+fn cache_file_day(path: &Path) -> Option<u8> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("day_")
+        .filter(|day| day.len() == 2)?
+        .parse()
+        .ok()
+        .filter(|day| (1..=25).contains(day))
+}
+
 pub async fn aoc_client() -> Result<ClientWithMiddleware> {
     let session = &get_config().aoc_session;
     let mut session_cookie = header::HeaderValue::from_str(&format!("session={}", session))
@@ -59,3 +444,120 @@ pub async fn aoc_client() -> Result<ClientWithMiddleware> {
         .build();
     Ok(client)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_error_bodies() {
+        let logged_out = "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n";
+        assert!(check_response(1, StatusCode::BAD_REQUEST, logged_out).is_err());
+        assert!(check_response(1, StatusCode::OK, logged_out).is_err());
+        let locked = "Please don't repeatedly request this endpoint before it unlocks! \
+            The calendar countdown is synchronized with the server time; \
+            the link will be enabled on the calendar the instant this puzzle becomes available.\n";
+        assert!(check_response(25, StatusCode::NOT_FOUND, locked)
+            .unwrap_err()
+            .to_string()
+            .contains("not unlocked"));
+        assert!(check_response(1, StatusCode::OK, "<!DOCTYPE html>\n<html>").is_err());
+        assert!(check_response(1, StatusCode::OK, "").is_err());
+        assert!(check_response(1, StatusCode::OK, "1abc2\npqr3stu8vwx\n").is_ok());
+    }
+
+    #[test]
+    fn test_doctor_flags_suspicious_files() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc_doctor_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        write_atomically(&dir.join("day_01"), b"1abc2\n")?;
+        write_atomically(&dir.join("day_02"), b"404 Not Found\n")?;
+        fs::write(dir.join("day_03.tmp"), "partial")?;
+        fs::write(dir.join("notes.txt"), "hi")?;
+        CacheMetadata::new(1, "1abc2\n", "me").save(&dir.join("day_01"))?;
+        CacheMetadata::new(2, "404 Not Found\n", "me").save(&dir.join("day_02"))?;
+        write_atomically(&dir.join("day_04"), b"0 3 6 9\n")?;
+        CacheMetadata::new(4, "1 2 3\n", "me").save(&dir.join("day_04"))?;
+        fs::write(dir.join("day_05.meta.json"), "{}")?;
+        let reports = cache_doctor_with_key(&dir, None)?;
+        fs::remove_dir_all(&dir)?;
+
+        let flagged = reports
+            .iter()
+            .map(|r| {
+                let name = r.path.file_name().unwrap().to_string_lossy().to_string();
+                (name, r.problem.is_some())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flagged,
+            vec![
+                ("day_01".to_owned(), false),
+                ("day_02".to_owned(), true),
+                ("day_03.tmp".to_owned(), true),
+                ("day_04".to_owned(), true),
+                ("day_05.meta.json".to_owned(), true),
+                ("notes.txt".to_owned(), true),
+            ]
+        );
+        assert_eq!(
+            reports[0]
+                .metadata
+                .as_ref()
+                .map(|m| m.session_fingerprint.clone()),
+            Some(session_fingerprint("me"))
+        );
+        assert_eq!(
+            reports[3].problem.as_deref(),
+            Some("content does not match its recorded sha256")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_checks() {
+        let metadata = CacheMetadata::new(7, "32T3K 765\n", "abc");
+        assert_eq!(metadata.check(7, "32T3K 765\n"), None);
+        assert!(metadata.check(7, "32T3K 766\n").is_some());
+        assert!(metadata.check(8, "32T3K 765\n").is_some());
+        assert_eq!(metadata.session_fingerprint.len(), 16);
+        assert_ne!(session_fingerprint("abc"), session_fingerprint("abd"));
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_encrypted_cache_round_trip() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc_encrypt_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let key_path = dir.with_extension("key").join("key");
+        let key = CacheKey::load_or_create(&key_path)?;
+        let input = "1abc2\npqr3stu8vwx\n";
+        write_cached(&dir.join("day_01"), input, None)?;
+        CacheMetadata::new(1, input, "me").save(&dir.join("day_01"))?;
+        write_cached(&dir.join("day_02"), "Game 1: 3 blue\n", Some(&key))?;
+
+        let encrypted = encrypt_cache_in(&dir, &key)?;
+        let on_disk = fs::read(dir.join("day_01"))?;
+        let reports = cache_doctor_with_key(&dir, Some(&key))?;
+        let without_key = read_cached(&dir.join("day_01"), &KeySource::Given(None));
+        let other_key = CacheKey(ChaCha20Poly1305::generate_key(&mut OsRng));
+        let wrong_key = read_cached(&dir.join("day_01"), &KeySource::Given(Some(&other_key)));
+        let reloaded = CacheKey::load(&key_path)?.ok_or("no key")?;
+        let decrypted = read_cached(&dir.join("day_01"), &KeySource::Given(Some(&reloaded)))?;
+        fs::remove_dir_all(&dir)?;
+        fs::remove_dir_all(dir.with_extension("key"))?;
+
+        assert_eq!(encrypted, vec![dir.join("day_01")]);
+        assert!(is_encrypted(&on_disk));
+        assert!(!String::from_utf8_lossy(&on_disk).contains("pqr3stu8vwx"));
+        assert_eq!(decrypted, input);
+        assert!(without_key.is_err());
+        assert!(wrong_key.is_err());
+        // The doctor reads through the encryption and the sidecar checksum still matches.
+        assert!(reports.iter().all(|r| r.problem.is_none()), "{reports:?}");
+        Ok(())
+    }
+}
@@ -4,6 +4,14 @@ static CONFIG: OnceLock<Config> = OnceLock::new();
 
 pub struct Config {
     pub aoc_session: String,
+    /// Encrypt newly cached inputs, `AOC_CACHE_ENCRYPT=1`.
+    pub cache_encryption: bool,
+    /// Key for the encrypted cache, `AOC_KEY_FILE`, `~` is expanded.
+    pub aoc_key_file: String,
+    /// Print the extra views some days have (renders, traces, samples), `AOC_DEBUG=1`.
+    pub debug: bool,
+    /// Where days that can draw their graph write it as Graphviz DOT, `AOC_DOT_FILE`.
+    pub dot_file: Option<String>,
 }
 
 impl Config {}
@@ -12,9 +20,19 @@ fn get_var(var_name: &str) -> String {
     var(var_name).unwrap_or("".to_owned())
 }
 
+fn is_set(value: &str) -> bool {
+    !matches!(value, "" | "0" | "false")
+}
+
 pub fn get_config() -> &'static Config {
     let config = CONFIG.get_or_init(|| Config {
         aoc_session: get_var("AOC_SESSION"),
+        cache_encryption: is_set(&get_var("AOC_CACHE_ENCRYPT")),
+        aoc_key_file: Some(get_var("AOC_KEY_FILE"))
+            .filter(|path| !path.is_empty())
+            .unwrap_or("~/.config/aoc/key".to_owned()),
+        debug: is_set(&get_var("AOC_DEBUG")),
+        dot_file: Some(get_var("AOC_DOT_FILE")).filter(|path| !path.is_empty()),
     });
     config
 }
@@ -0,0 +1,284 @@
+pub mod rational;
+pub mod sequence;
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn gcd_u128(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd_u128(b, a % b)
+    }
+}
+
+/// `None` if the lcm does not fit in a `u64`.
+pub fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+pub fn checked_lcm_u128(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd_u128(a, b)).checked_mul(b)
+}
+
+/// `None` for an empty slice.
+pub fn find_gcd(numbers: &[u64]) -> Option<u64> {
+    numbers.iter().copied().reduce(gcd)
+}
+
+/// Same as [`find_gcd`] for `u128` values. `None` for an empty slice.
+pub fn find_gcd_u128(numbers: &[u128]) -> Option<u128> {
+    numbers.iter().copied().reduce(gcd_u128)
+}
+
+/// `None` for an empty slice or when the lcm overflows a `u64`.
+pub fn find_lcm(numbers: &[u64]) -> Option<u64> {
+    let (first, rest) = numbers.split_first()?;
+    rest.iter()
+        .try_fold(*first, |acc, num| checked_lcm(acc, *num))
+}
+
+/// Same as [`find_lcm`] with `u128` headroom for when the `u64` version overflows.
+pub fn find_lcm_u128(numbers: &[u64]) -> Option<u128> {
+    let (first, rest) = numbers.split_first()?;
+    rest.iter().try_fold(*first as u128, |acc, num| {
+        checked_lcm_u128(acc, *num as u128)
+    })
+}
+
+/// Returns `(g, x, y)` where `g = gcd(a, b) >= 0` and `a*x + b*y = g`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `x` in `0..m` with `a*x ≡ 1 (mod m)`, `None` when `a` and `m` share a factor.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Solves `x ≡ residue (mod modulus)` for every `(residue, modulus)` pair, returning the smallest
+/// non negative `x` and the combined modulus. Moduli do not need to be coprime, `None` means the
+/// system has no solution (or the combined modulus overflows).
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences
+        .iter()
+        .try_fold((0i128, 1i128), |(r1, m1), &(r2, m2)| {
+            if m2 <= 0 {
+                return None;
+            }
+            let r2 = r2.rem_euclid(m2);
+            let (g, _, _) = extended_gcd(m1, m2);
+            let diff = r2 - r1;
+            if diff % g != 0 {
+                return None;
+            }
+            let m2_g = m2 / g;
+            let lcm = (m1 / g).checked_mul(m2)?;
+            // m1*k ≡ diff (mod m2) reduces to (m1/g)*k ≡ diff/g (mod m2/g)
+            let inv = mod_inverse(m1 / g, m2_g)?;
+            let k = (diff / g).rem_euclid(m2_g).checked_mul(inv)? % m2_g;
+            let x = r1.checked_add(m1.checked_mul(k)?)?.rem_euclid(lcm);
+            Some((x, lcm))
+        })
+}
+
+/// Floor of the square root of `n`, exact for the whole `u128` range.
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method from an overestimate converges down onto the floor root.
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Smallest and largest integer `x` with `a*x^2 + b*x + c < 0`, for an upward parabola (`a > 0`).
+/// `None` when no integer lands strictly between the roots.
+///
+/// The integer square root of the discriminant puts each guess within one of the real bound,
+/// exact evaluation of the polynomial then settles it without any floating point.
+pub fn quadratic_integer_bounds(a: i128, b: i128, c: i128) -> Option<(i128, i128)> {
+    if a <= 0 {
+        return None;
+    }
+    let discriminant = b
+        .checked_mul(b)?
+        .checked_sub(a.checked_mul(c)?.checked_mul(4)?)?;
+    if discriminant < 0 {
+        return None;
+    }
+    let root = isqrt(discriminant as u128) as i128;
+    let below = |x: i128| a * x * x + b * x + c < 0;
+
+    let mut low = (-b - root).div_euclid(2 * a);
+    let mut high = (-b + root).div_euclid(2 * a) + 1;
+    while low <= high && !below(low) {
+        low += 1;
+    }
+    if low > high {
+        return None;
+    }
+    while below(low - 1) {
+        low -= 1;
+    }
+    while !below(high) {
+        high -= 1;
+    }
+    while below(high + 1) {
+        high += 1;
+    }
+    Some((low, high))
+}
+
+pub fn orthogonal_u_bounded(root: (usize, usize)) -> Vec<((usize, usize), (isize, isize))> {
+    [(0, 1), (0, -1), (1, 0), (-1, 0)]
+        .into_iter()
+        .filter_map(|delta| {
+            root.0
+                .checked_add_signed(delta.0)
+                .zip(root.1.checked_add_signed(delta.1))
+                .map(|new_pos| (new_pos, delta))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn gcd_and_lcm_divide(a in 1u64..1_000_000, b in 1u64..1_000_000) {
+            let g = gcd(a, b);
+            prop_assert_eq!(a % g, 0);
+            prop_assert_eq!(b % g, 0);
+            let l = checked_lcm(a, b).unwrap();
+            prop_assert_eq!(l % a, 0);
+            prop_assert_eq!(l % b, 0);
+            prop_assert_eq!(g as u128 * l as u128, a as u128 * b as u128);
+        }
+
+        #[test]
+        fn slice_lcm_matches_u128(numbers in prop::collection::vec(1u64..u32::MAX as u64, 1..6)) {
+            let wide = find_lcm_u128(&numbers);
+            match find_lcm(&numbers) {
+                Some(l) => prop_assert_eq!(Some(l as u128), wide),
+                None => prop_assert!(wide.is_none_or(|w| w > u64::MAX as u128)),
+            }
+            let g = find_gcd(&numbers).unwrap();
+            prop_assert!(numbers.iter().all(|n| n % g == 0));
+        }
+
+        #[test]
+        fn slice_gcd_u128_divides(numbers in prop::collection::vec(1u128..u128::MAX, 1..6), factor in 1u128..1_000_000) {
+            let g = find_gcd_u128(&numbers).unwrap();
+            prop_assert!(numbers.iter().all(|n| n % g == 0));
+            let reduced = numbers.iter().map(|n| n / g).collect::<Vec<_>>();
+            prop_assert_eq!(find_gcd_u128(&reduced), Some(1));
+            let scaled = reduced.iter().map(|n| (n % (1 << 64)) * factor).collect::<Vec<_>>();
+            prop_assert_eq!(find_gcd_u128(&scaled).unwrap() % factor, 0);
+            let narrow = numbers.iter().map(|n| (n % u64::MAX as u128) as u64 + 1).collect::<Vec<_>>();
+            let wide = narrow.iter().map(|n| *n as u128).collect::<Vec<_>>();
+            prop_assert_eq!(find_gcd(&narrow).map(u128::from), find_gcd_u128(&wide));
+        }
+
+        #[test]
+        fn extended_gcd_identity(a in -1_000_000_000i128..1_000_000_000, b in -1_000_000_000i128..1_000_000_000) {
+            let (g, x, y) = extended_gcd(a, b);
+            prop_assert_eq!(a * x + b * y, g);
+            prop_assert_eq!(g as u128, gcd_u128(a.unsigned_abs(), b.unsigned_abs()));
+        }
+
+        #[test]
+        fn mod_inverse_inverts(a in -10_000i128..10_000, m in 1i128..10_000) {
+            match mod_inverse(a, m) {
+                Some(inv) => {
+                    prop_assert!((0..m).contains(&inv));
+                    prop_assert_eq!((a * inv).rem_euclid(m), 1 % m);
+                }
+                None => prop_assert_ne!(extended_gcd(a, m).0, 1),
+            }
+        }
+
+        #[test]
+        fn crt_matches_brute_force(congruences in prop::collection::vec((0i128..50, 1i128..12), 1..4)) {
+            let modulus: i128 = congruences.iter().fold(1, |acc, (_, m)| {
+                checked_lcm_u128(acc as u128, *m as u128).unwrap() as i128
+            });
+            let brute = (0..modulus).find(|x| congruences.iter().all(|(r, m)| x % m == r % m));
+            prop_assert_eq!(crt(&congruences), brute.map(|x| (x, modulus)));
+        }
+
+        #[test]
+        fn isqrt_is_floor(n in any::<u128>()) {
+            let r = isqrt(n);
+            prop_assert!(r * r <= n);
+            prop_assert!((r + 1).checked_mul(r + 1).is_none_or(|sq| sq > n));
+        }
+
+        #[test]
+        fn quadratic_bounds_match_brute_force(a in 1i128..5, b in -60i128..60, c in -60i128..60) {
+            let below: Vec<i128> = (-100..100).filter(|x| a * x * x + b * x + c < 0).collect();
+            let expected = below.first().copied().zip(below.last().copied());
+            prop_assert_eq!(quadratic_integer_bounds(a, b, c), expected);
+        }
+    }
+
+    #[test]
+    fn test_lcm_overflow_is_reported() {
+        let primes = [4_294_967_291, 4_294_967_279, 4_294_967_231];
+        assert_eq!(find_lcm(&primes), None);
+        assert_eq!(
+            find_lcm_u128(&primes[..2]),
+            Some(4_294_967_291u128 * 4_294_967_279)
+        );
+        assert_eq!(find_lcm(&[]), None);
+        assert_eq!(find_gcd_u128(&[]), None);
+        assert_eq!(
+            find_gcd_u128(&[u128::MAX, u128::MAX / 3]),
+            Some(u128::MAX / 3)
+        );
+    }
+
+    #[test]
+    fn test_isqrt_edges() {
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt((1 << 64) - 1), (1 << 32) - 1);
+        assert_eq!(isqrt(1 << 64), 1 << 32);
+    }
+}
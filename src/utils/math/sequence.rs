@@ -0,0 +1,177 @@
+use super::rational::Rational;
+
+/// Repeated finite differences of `values`. The first row is `values` itself and rows stop once
+/// one is all zero (that row is included) or only a single value is left.
+pub fn differences(values: &[i64]) -> Vec<Vec<i64>> {
+    let mut rows = vec![values.to_vec()];
+    while let Some(last) = rows.last() {
+        if last.len() <= 1 || last.iter().all(|v| *v == 0) {
+            break;
+        }
+        let next = last.windows(2).map(|w| w[1] - w[0]).collect();
+        rows.push(next);
+    }
+    rows
+}
+
+/// Value of the lowest degree polynomial through `values[0..n]` (taken at `x = 0..n`) at any
+/// integer `x`, negative or past the end.
+///
+/// Uses Newton's forward difference form `f(x) = Σ Δ^j f(0) * C(x, j)`, where `C(x, j)` stays an
+/// integer for every integer `x` so nothing needs to leave integer math.
+pub fn value_at(values: &[i64], x: i64) -> i128 {
+    let x = x as i128;
+    let mut binomial: i128 = 1;
+    let mut total: i128 = 0;
+    for (j, row) in differences(values).iter().enumerate() {
+        if j > 0 {
+            binomial = binomial * (x - j as i128 + 1) / j as i128;
+        }
+        if let Some(first) = row.first() {
+            total += *first as i128 * binomial;
+        }
+    }
+    total
+}
+
+/// Value `steps` past the last entry, `steps = 1` being the next one in the sequence.
+pub fn extrapolate_forward(values: &[i64], steps: usize) -> i128 {
+    value_at(values, values.len() as i64 - 1 + steps as i64)
+}
+
+/// Value `steps` before the first entry, `steps = 1` being the one just before it.
+pub fn extrapolate_backward(values: &[i64], steps: usize) -> i128 {
+    value_at(values, -(steps as i64))
+}
+
+/// Polynomial with exact rational coefficients, lowest degree first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    coefficients: Vec<Rational>,
+}
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<Rational>) -> Self {
+        while coefficients.last().is_some_and(Rational::is_zero) {
+            coefficients.pop();
+        }
+        Self { coefficients }
+    }
+
+    /// Lagrange interpolation through arbitrary `(x, y)` points. `None` if two points share an
+    /// `x`.
+    pub fn interpolate(points: &[(i64, i64)]) -> Option<Self> {
+        let mut coefficients = vec![Rational::ZERO; points.len()];
+        for (i, &(xi, yi)) in points.iter().enumerate() {
+            // Build the basis polynomial Π (x - xj) / (xi - xj) for j != i.
+            let mut basis = vec![Rational::ONE];
+            let mut denominator = Rational::ONE;
+            for (j, &(xj, _)) in points.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                if xi == xj {
+                    return None;
+                }
+                let mut next = vec![Rational::ZERO; basis.len() + 1];
+                for (k, c) in basis.iter().enumerate() {
+                    next[k + 1] = next[k + 1] + *c;
+                    next[k] = next[k] - *c * Rational::from(xj);
+                }
+                basis = next;
+                denominator = denominator * Rational::from(xi - xj);
+            }
+            let scale = Rational::from(yi) / denominator;
+            for (k, c) in basis.into_iter().enumerate() {
+                coefficients[k] = coefficients[k] + c * scale;
+            }
+        }
+        Some(Self::new(coefficients))
+    }
+
+    /// Newton interpolation of `values` taken at `x = 0..n`, expanding
+    /// `Σ Δ^j f(0) * x(x-1)..(x-j+1) / j!` into plain coefficients.
+    pub fn from_sequence(values: &[i64]) -> Self {
+        let mut coefficients = vec![Rational::ZERO];
+        // falling factorial x(x-1)..(x-j+1) / j! as coefficients
+        let mut term = vec![Rational::ONE];
+        for (j, row) in differences(values).iter().enumerate() {
+            if j > 0 {
+                let mut next = vec![Rational::ZERO; term.len() + 1];
+                let shift = Rational::from(j as i64 - 1);
+                for (k, c) in term.iter().enumerate() {
+                    next[k + 1] = next[k + 1] + *c;
+                    next[k] = next[k] - *c * shift;
+                }
+                let j = Rational::from(j as i64);
+                term = next.into_iter().map(|c| c / j).collect();
+            }
+            let Some(first) = row.first() else {
+                break;
+            };
+            coefficients.resize(term.len().max(coefficients.len()), Rational::ZERO);
+            for (k, c) in term.iter().enumerate() {
+                coefficients[k] = coefficients[k] + *c * Rational::from(*first);
+            }
+        }
+        Self::new(coefficients)
+    }
+
+    pub fn coefficients(&self) -> &[Rational] {
+        &self.coefficients
+    }
+
+    /// Degree of the polynomial, `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn eval(&self, x: Rational) -> Rational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Rational::ZERO, |acc, c| acc * x + *c)
+    }
+
+    /// Evaluates at an integer `x`, `None` if the result is not an integer.
+    pub fn eval_integer(&self, x: i64) -> Option<i128> {
+        self.eval(Rational::from(x)).to_integer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extrapolate_both_ways() {
+        let values = [10, 13, 16, 21, 30, 45];
+        assert_eq!(extrapolate_forward(&values, 1), 68);
+        assert_eq!(extrapolate_backward(&values, 1), 5);
+        assert_eq!(differences(&values).len(), 5);
+        // n^2 sampled from n = 0
+        let squares = [0, 1, 4];
+        assert_eq!(extrapolate_forward(&squares, 7), 81);
+        assert_eq!(extrapolate_backward(&squares, 3), 9);
+    }
+
+    #[test]
+    fn test_polynomial_fits_agree() {
+        let values = [1, 3, 6, 10, 15, 21];
+        let newton = Polynomial::from_sequence(&values);
+        let points = values
+            .iter()
+            .enumerate()
+            .map(|(x, y)| (x as i64, *y))
+            .collect::<Vec<_>>();
+        let lagrange = Polynomial::interpolate(&points).unwrap();
+        assert_eq!(newton, lagrange);
+        assert_eq!(newton.degree(), Some(2));
+        assert_eq!(
+            newton.coefficients(),
+            &[Rational::ONE, Rational::new(3, 2), Rational::new(1, 2)]
+        );
+        assert_eq!(newton.eval_integer(-3), Some(value_at(&values, -3)));
+        assert_eq!(Polynomial::interpolate(&[(1, 1), (1, 2)]), None);
+    }
+}
@@ -3,3 +3,4 @@ pub mod common;
 pub mod config;
 pub mod grid;
 pub mod math;
+pub mod sparse_grid;
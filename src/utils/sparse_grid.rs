@@ -0,0 +1,250 @@
+use grid::Grid;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+/// Signed `(row, col)` position in a [`SparseGrid`].
+pub type Pos = (i64, i64);
+
+/// Inclusive `(min, max)` corners of everything currently stored in a [`SparseGrid`].
+pub type Bounds = (Pos, Pos);
+
+/// Grid that only stores occupied cells, for puzzles whose coordinates go negative or are too
+/// large to allocate densely. Keeps a per row/col occupancy count so bounds and empty row/col
+/// lookups stay correct after removals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Pos, T>,
+    rows: BTreeMap<i64, usize>,
+    cols: BTreeMap<i64, usize>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+            rows: BTreeMap::new(),
+            cols: BTreeMap::new(),
+        }
+    }
+}
+
+/// Runs of keys missing between consecutive occupied keys, ascending.
+fn gaps(counts: &BTreeMap<i64, usize>) -> impl Iterator<Item = RangeInclusive<i64>> + '_ {
+    counts
+        .keys()
+        .zip(counts.keys().skip(1))
+        .filter(|(a, b)| *b - *a > 1)
+        .map(|(a, b)| a + 1..=b - 1)
+}
+
+fn decrement(counts: &mut BTreeMap<i64, usize>, key: i64) {
+    if let Some(count) = counts.get_mut(&key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(&key);
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Stores `value` at `pos`, returning whatever was there before.
+    pub fn insert(&mut self, pos: Pos, value: T) -> Option<T> {
+        let previous = self.cells.insert(pos, value);
+        if previous.is_none() {
+            *self.rows.entry(pos.0).or_default() += 1;
+            *self.cols.entry(pos.1).or_default() += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, pos: &Pos) -> Option<T> {
+        let removed = self.cells.remove(pos);
+        if removed.is_some() {
+            decrement(&mut self.rows, pos.0);
+            decrement(&mut self.cols, pos.1);
+        }
+        removed
+    }
+
+    pub fn get(&self, pos: &Pos) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn get_mut(&mut self, pos: &Pos) -> Option<&mut T> {
+        self.cells.get_mut(pos)
+    }
+
+    pub fn contains(&self, pos: &Pos) -> bool {
+        self.cells.contains_key(pos)
+    }
+
+    /// Occupied cells in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Pos, &T)> {
+        self.cells.iter()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Pos> {
+        self.cells.keys()
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        let (min_row, max_row) = self.rows.keys().next().zip(self.rows.keys().next_back())?;
+        let (min_col, max_col) = self.cols.keys().next().zip(self.cols.keys().next_back())?;
+        Some(((*min_row, *min_col), (*max_row, *max_col)))
+    }
+
+    /// Number of occupied cells in `row`.
+    pub fn row_count(&self, row: i64) -> usize {
+        self.rows.get(&row).copied().unwrap_or(0)
+    }
+
+    /// Number of occupied cells in `col`.
+    pub fn col_count(&self, col: i64) -> usize {
+        self.cols.get(&col).copied().unwrap_or(0)
+    }
+
+    /// Rows with at least one cell, ascending.
+    pub fn occupied_rows(&self) -> impl Iterator<Item = i64> + '_ {
+        self.rows.keys().copied()
+    }
+
+    /// Cols with at least one cell, ascending.
+    pub fn occupied_cols(&self) -> impl Iterator<Item = i64> + '_ {
+        self.cols.keys().copied()
+    }
+
+    /// Runs of rows inside the bounding box that have no cells, ascending. Found from the gaps
+    /// between occupied rows, so this is O(occupied rows) however far apart they are.
+    pub fn empty_rows(&self) -> impl Iterator<Item = RangeInclusive<i64>> + '_ {
+        gaps(&self.rows)
+    }
+
+    /// Runs of cols inside the bounding box that have no cells, ascending. O(occupied cols).
+    pub fn empty_cols(&self) -> impl Iterator<Item = RangeInclusive<i64>> + '_ {
+        gaps(&self.cols)
+    }
+
+    /// Builds a sparse grid from a dense one, keeping only cells that pass `keep`. Dense `(row,
+    /// col)` maps directly to the same signed position.
+    pub fn from_grid(grid: &Grid<T>, keep: impl Fn(&T) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        grid.indexed_iter()
+            .filter(|(_, t)| keep(t))
+            .map(|((row, col), t)| ((row as i64, col as i64), t.clone()))
+            .collect()
+    }
+
+    /// Dense copy of the bounding box with unoccupied cells set to `fill`. Also returns the
+    /// signed position of the dense `(0, 0)` cell so callers can map back. O(area of the bounding
+    /// box), so only for grids known to be small.
+    pub fn to_grid(&self, fill: T) -> (Grid<T>, Pos)
+    where
+        T: Clone,
+    {
+        match self.bounds() {
+            Some((min, max)) => {
+                let rows = (max.0 - min.0 + 1) as usize;
+                let cols = (max.1 - min.1 + 1) as usize;
+                let mut grid = Grid::init(rows, cols, fill);
+                for (pos, t) in self.cells.iter() {
+                    if let Some(cell) =
+                        grid.get_mut((pos.0 - min.0) as usize, (pos.1 - min.1) as usize)
+                    {
+                        *cell = t.clone();
+                    }
+                }
+                (grid, min)
+            }
+            None => (Grid::init(0, 0, fill), (0, 0)),
+        }
+    }
+
+    /// Draws the bounding box one char per cell, top row first. O(area of the bounding box) like
+    /// [`Self::to_grid`].
+    pub fn render(&self, to_char: impl Fn(Pos, Option<&T>) -> char) -> String {
+        let mut out = String::new();
+        if let Some((min, max)) = self.bounds() {
+            for row in min.0..=max.0 {
+                for col in min.1..=max.1 {
+                    out.push(to_char((row, col), self.cells.get(&(row, col))));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (pos, t) in iter {
+            grid.insert(pos, t);
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_and_occupancy() {
+        let mut grid: SparseGrid<char> = [((-2, 5), 'a'), ((3, -1), 'b'), ((0, 0), 'c')]
+            .into_iter()
+            .collect();
+        assert_eq!(grid.bounds(), Some(((-2, -1), (3, 5))));
+        assert_eq!(grid.empty_rows().collect::<Vec<_>>(), vec![-1..=-1, 1..=2]);
+        assert_eq!(grid.empty_cols().collect::<Vec<_>>(), vec![1..=4]);
+
+        grid.remove(&(-2, 5));
+        assert_eq!(grid.bounds(), Some(((0, -1), (3, 0))));
+        assert_eq!(grid.row_count(-2), 0);
+        assert_eq!(grid.col_count(0), 1);
+    }
+
+    #[test]
+    fn test_empty_lines_far_apart() {
+        let grid: SparseGrid<()> = [((0, -1_000_000_000_000), ()), ((1_000_000_000_000, 7), ())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            grid.empty_rows().collect::<Vec<_>>(),
+            vec![1..=999_999_999_999]
+        );
+        assert_eq!(
+            grid.empty_cols().collect::<Vec<_>>(),
+            vec![-999_999_999_999..=6]
+        );
+    }
+
+    #[test]
+    fn test_dense_round_trip() {
+        let mut grid = SparseGrid::new();
+        grid.insert((-1, -1), '#');
+        grid.insert((1, 2), '#');
+        assert_eq!(grid.render(|_, t| *t.unwrap_or(&'.')), "#...\n....\n...#\n");
+
+        let (dense, origin) = grid.to_grid('.');
+        assert_eq!(origin, (-1, -1));
+        assert_eq!(dense.size(), (3, 4));
+        let back = SparseGrid::from_grid(&dense, |c| *c == '#');
+        assert_eq!(back.positions().count(), 2);
+        assert!(back.contains(&(0, 0)) && back.contains(&(2, 3)));
+    }
+}